@@ -0,0 +1,405 @@
+//! Browse the examples in the terminal
+//!
+//! The examples are listed by title, tags and score, and filtered by a fuzzy search
+//! as the reader types. An opened example shows `## Before` and `## After` side by side,
+//! with the lines which change highlighted, and steps through the list explaining its command.
+//!
+//! The [`Browser`] only draws text with escape sequences, the caller puts the terminal
+//! into raw mode and feeds it the keys, see [`crate::pty::RawMode`] and [`crate::pty::decode`].
+
+use crate::{
+    golf::notation,
+    parse_example::Example,
+    parse_helix_keys::{KeyCode, KeyEvent, KeyModifiers},
+    score::Score,
+};
+
+/// Escape sequences of the styles
+mod style {
+    /// Back to the default style
+    pub const RESET: &str = "\x1b[0m";
+    /// Bold text
+    pub const BOLD: &str = "\x1b[1m";
+    /// Dimmed text
+    pub const DIM: &str = "\x1b[2m";
+    /// Swapped foreground and background, such as for the selected example
+    pub const REVERSE: &str = "\x1b[7m";
+    /// Lines of `## Before` which are not in `## After`
+    pub const REMOVED: &str = "\x1b[31m";
+    /// Lines of `## After` which are not in `## Before`
+    pub const ADDED: &str = "\x1b[32m";
+}
+
+/// State of the terminal browser
+pub struct Browser {
+    /// All of the examples, in the order they are listed without a search
+    examples: Vec<Example>,
+    /// Score of each example
+    scores: Vec<Score>,
+    /// The fuzzy search
+    query: String,
+    /// Indexes of the examples which match the query, best match first
+    matches: Vec<usize>,
+    /// Index into `matches` of the selected example
+    selected: usize,
+    /// The step of the selected example which is shown, if the example is open
+    step: Option<usize>,
+    /// Whether the reader has asked to quit
+    quit: bool,
+}
+
+impl Browser {
+    /// Browse the `examples`, listed in this order until the reader searches
+    pub fn new(examples: Vec<Example>) -> Self {
+        let mut browser = Self {
+            scores: examples.iter().map(Example::score).collect(),
+            matches: Vec::new(),
+            examples,
+            query: String::new(),
+            selected: 0,
+            step: None,
+            quit: false,
+        };
+        browser.search();
+        browser
+    }
+
+    /// Whether the reader has asked to quit
+    pub const fn has_quit(&self) -> bool {
+        self.quit
+    }
+
+    /// React to the `key` pressed by the reader
+    pub fn press(&mut self, key: &KeyEvent) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if ctrl && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+
+        match self.step {
+            // An example is open
+            Some(step) => {
+                let steps = self
+                    .selected_example()
+                    .map_or(0, |example| example.steps.len());
+                match key.code {
+                    KeyCode::Right | KeyCode::Down | KeyCode::Char('l' | 'j' | 'n' | ' ') => {
+                        self.step = Some((step + 1).min(steps.saturating_sub(1)));
+                    }
+                    KeyCode::Left | KeyCode::Up | KeyCode::Char('h' | 'k' | 'p') => {
+                        self.step = Some(step.saturating_sub(1));
+                    }
+                    KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('q') => self.step = None,
+                    _ => {}
+                }
+            }
+            None => match key.code {
+                KeyCode::Down | KeyCode::Tab => {
+                    self.selected = (self.selected + 1).min(self.matches.len().saturating_sub(1));
+                }
+                KeyCode::Char('n') if ctrl => {
+                    self.selected = (self.selected + 1).min(self.matches.len().saturating_sub(1));
+                }
+                KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+                KeyCode::Char('p') if ctrl => self.selected = self.selected.saturating_sub(1),
+                KeyCode::Enter if !self.matches.is_empty() => self.step = Some(0),
+                KeyCode::Esc if self.query.is_empty() => self.quit = true,
+                KeyCode::Esc => {
+                    self.query.clear();
+                    self.search();
+                }
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.search();
+                }
+                KeyCode::Char(ch) if !ctrl && !key.modifiers.contains(KeyModifiers::ALT) => {
+                    self.query.push(ch);
+                    self.search();
+                }
+                _ => {}
+            },
+        }
+    }
+
+    /// The screen, `cols` wide and `rows` high, as text with escape sequences
+    pub fn render(&self, cols: usize, rows: usize) -> String {
+        let lines = match (self.step, self.selected_example()) {
+            (Some(step), Some(example)) => self.render_example(example, step, cols, rows),
+            _ => self.render_list(cols, rows),
+        };
+
+        let mut screen = String::from("\x1b[H\x1b[2J");
+        for (i, line) in lines.iter().take(rows).enumerate() {
+            if i != 0 {
+                screen.push_str("\r\n");
+            }
+            screen.push_str(line);
+            screen.push_str(style::RESET);
+        }
+        screen
+    }
+
+    /// The example which is selected in the list
+    fn selected_example(&self) -> Option<&Example> {
+        self.matches
+            .get(self.selected)
+            .map(|&index| &self.examples[index])
+    }
+
+    /// Find the examples which match the query
+    fn search(&mut self) {
+        let mut matches = self
+            .examples
+            .iter()
+            .enumerate()
+            .filter_map(|(i, example)| {
+                let text = format!("{} {}", example.title, example.tags.join(" "));
+                fuzzy_match(&self.query, &text).map(|score| (score, i))
+            })
+            .collect::<Vec<_>>();
+        // Stable, so equally good matches keep the order of the list
+        matches.sort_by_key(|(score, _)| *score);
+        self.matches = matches.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+    }
+
+    /// Lines of the list of examples
+    fn render_list(&self, cols: usize, rows: usize) -> Vec<String> {
+        let mut lines = vec![
+            format!(
+                "{}Helix Golf{} {}{} of {} examples{}",
+                style::BOLD,
+                style::RESET,
+                style::DIM,
+                self.matches.len(),
+                self.examples.len(),
+                style::RESET
+            ),
+            format!("> {}{}_{}", self.query, style::DIM, style::RESET),
+            String::new(),
+        ];
+
+        // As wide as the longest title and tags, leaving room for the score
+        let width = |text: fn(&Example) -> String| {
+            self.examples
+                .iter()
+                .map(|example| text(example).chars().count())
+                .max()
+                .unwrap_or_default()
+                .min(cols / 3)
+        };
+        let title_width = width(|example| example.title.clone());
+        let tags_width = width(|example| example.tags.join(", "));
+        // Keep the selected example on the screen
+        let height = rows.saturating_sub(lines.len() + 2).max(1);
+        let first = self.selected.saturating_sub(height - 1);
+
+        for (i, &index) in self.matches.iter().enumerate().skip(first).take(height) {
+            let example = &self.examples[index];
+            let row = format!(
+                "{} {} {}",
+                pad(&example.title, title_width),
+                pad(&example.tags.join(", "), tags_width),
+                self.scores[index]
+            );
+            lines.push(if i == self.selected {
+                format!("{}> {}", style::REVERSE, pad(&row, cols.saturating_sub(2)))
+            } else {
+                format!("  {}", truncate(&row, cols.saturating_sub(2)))
+            });
+        }
+        if self.matches.is_empty() {
+            lines.push(format!(
+                "{}  No example matches{}",
+                style::DIM,
+                style::RESET
+            ));
+        }
+
+        while lines.len() < rows.saturating_sub(1) {
+            lines.push(String::new());
+        }
+        lines.push(footer(
+            "type to search  up/down select  enter open  esc clear or quit",
+            cols,
+        ));
+        lines
+    }
+
+    /// Lines of the opened `example`, showing its `step`
+    fn render_example(
+        &self,
+        example: &Example,
+        step: usize,
+        cols: usize,
+        rows: usize,
+    ) -> Vec<String> {
+        let score = self.scores[self.matches[self.selected]];
+        let mut lines = vec![format!(
+            "{}{}{}  {}{}  {score}{}",
+            style::BOLD,
+            example.title,
+            style::RESET,
+            style::DIM,
+            example.tags.join(", "),
+            style::RESET
+        )];
+        if let Some(description) = &example.description {
+            lines.extend(wrap(description, cols));
+        }
+        lines.push(String::new());
+
+        // The command and the step, below `## Before` and `## After`
+        let mut command = vec![format!("{}Command{}", style::BOLD, style::RESET)];
+        let current = example.steps.get(step);
+        let range = current.map_or(0..0, |step| step.key_events.clone());
+        let keys = &example.key_events;
+        // Broken between keys, with the keys of the step highlighted
+        let mut line = (String::new(), 0);
+        for (i, key) in keys.iter().enumerate() {
+            let key = key.notation();
+            if line.1 != 0 && line.1 + key.len() > cols {
+                command.push(std::mem::take(&mut line).0);
+            }
+            if range.contains(&i) {
+                line.0.push_str(style::REVERSE);
+                line.0.push_str(&key);
+                line.0.push_str(style::RESET);
+            } else {
+                line.0.push_str(&key);
+            }
+            line.1 += key.len();
+        }
+        command.push(line.0);
+        command.push(String::new());
+        if let Some(current) = current {
+            command.push(format!(
+                "{}Step {}/{}{}  {}",
+                style::BOLD,
+                step + 1,
+                example.steps.len(),
+                style::RESET,
+                notation(&keys[range])
+            ));
+            command.extend(wrap(current.text.trim(), cols));
+        }
+
+        // `## Before` and `## After`, side by side
+        let width = cols.saturating_sub(3) / 2;
+        let (before, after) = highlight(&example.before, &example.after);
+        let height = rows.saturating_sub(lines.len() + command.len() + 4).max(1);
+        lines.push(format!(
+            "{}{}{} | {}{}",
+            style::BOLD,
+            pad("Before", width),
+            style::RESET,
+            style::BOLD,
+            "After"
+        ));
+        for i in 0..before.len().max(after.len()).min(height) {
+            let column = |column: &[(Option<&str>, &str)]| {
+                column.get(i).map_or_else(
+                    || " ".repeat(width),
+                    |(color, line)| {
+                        format!(
+                            "{}{}{}",
+                            color.unwrap_or_default(),
+                            pad(line, width),
+                            style::RESET
+                        )
+                    },
+                )
+            };
+            lines.push(format!("{} | {}", column(&before), column(&after)));
+        }
+        if before.len().max(after.len()) > height {
+            lines.push(format!("{}...{}", style::DIM, style::RESET));
+        }
+        lines.push(String::new());
+        lines.extend(command);
+
+        while lines.len() < rows.saturating_sub(1) {
+            lines.push(String::new());
+        }
+        lines.push(footer(
+            "left/right previous or next step  esc back  ctrl-c quit",
+            cols,
+        ));
+        lines
+    }
+}
+
+/// How well the `query` matches the `text`, lower is better.
+///
+/// `None` if the characters of the query are not all in the text, in the same order.
+/// Ignores the case, and counts the characters skipped between the matching ones
+pub fn fuzzy_match(query: &str, text: &str) -> Option<usize> {
+    let text = text.to_lowercase();
+    let mut chars = text.chars();
+    let mut skipped = 0;
+    for wanted in query
+        .to_lowercase()
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+    {
+        skipped += chars.position(|ch| ch == wanted)?;
+    }
+    Some(skipped)
+}
+
+/// Lines of a column, with their color
+type Column<'a> = Vec<(Option<&'static str>, &'a str)>;
+
+/// Lines of `before` and `after`, with the color of those which are only in one of them
+fn highlight<'a>(before: &'a str, after: &'a str) -> (Column<'a>, Column<'a>) {
+    let (mut left, mut right) = (Vec::new(), Vec::new());
+    for line in diff::lines(before.trim_end(), after.trim_end()) {
+        match line {
+            diff::Result::Left(line) => left.push((Some(style::REMOVED), line)),
+            diff::Result::Right(line) => right.push((Some(style::ADDED), line)),
+            diff::Result::Both(before, after) => {
+                left.push((None, before));
+                right.push((None, after));
+            }
+        }
+    }
+    (left, right)
+}
+
+/// The `text`, cut or padded with spaces to `width` characters
+fn pad(text: &str, width: usize) -> String {
+    format!("{:width$}", truncate(text, width))
+}
+
+/// The `text`, cut to at most `width` characters
+fn truncate(text: &str, width: usize) -> String {
+    text.chars()
+        .map(|ch| if ch == '\t' { ' ' } else { ch })
+        .take(width)
+        .collect()
+}
+
+/// The `text` on lines of at most `width` characters, broken between words
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// The keys which can be pressed, at the bottom of the screen
+fn footer(help: &str, cols: usize) -> String {
+    format!("{}{}", style::DIM, truncate(help, cols))
+}
@@ -0,0 +1,58 @@
+//! [WebVTT](https://www.w3.org/TR/webvtt1/) captions of a demo, with a cue for each step
+//! of the explanation list
+
+use std::{fmt::Write as _, time::Duration};
+
+use crate::parse_example::Example;
+
+impl Example {
+    /// Name of the file with the captions, relative to the generated directory
+    pub fn captions_file(&self) -> String {
+        format!("{}.vtt", self.name)
+    }
+
+    /// Captions which show each step of the explanation list while its keys are typed.
+    ///
+    /// Step `i` starts at `starts[i]`, and the last step lasts until `end`
+    pub fn captions(&self, starts: &[Duration], end: Duration) -> String {
+        let ends = starts.iter().skip(1).copied().chain(std::iter::once(end));
+
+        self.steps
+            .iter()
+            .zip(starts.iter().copied().zip(ends))
+            .fold(String::from("WEBVTT\n"), |mut vtt, (step, (start, end))| {
+                let _ = write!(
+                    vtt,
+                    "\n{} --> {}\n{}\n",
+                    timestamp(start),
+                    timestamp(end),
+                    cue_text(&step.text)
+                );
+                vtt
+            })
+    }
+}
+
+/// `duration` as `hh:mm:ss.ttt`
+fn timestamp(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+/// Text of a cue from the markdown of a step, on a single line
+fn cue_text(markdown: &str) -> String {
+    markdown
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('`', "")
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
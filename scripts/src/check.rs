@@ -0,0 +1,108 @@
+//! Check keys which someone proposes for an example, such as a shorter command
+//!
+//! The keys are typed into Helix with [`crate::oracle`], and scored against the command
+//! of the example. If they work, they can be added to the example as an alternative.
+
+use std::fs;
+
+use miette::{ensure, miette};
+use pretty_assertions::StrComparison;
+
+use crate::{
+    generate_helix_config, oracle,
+    parse_example::Example,
+    parse_helix_keys::{KeyEvent, parse_keys},
+    paths::Paths,
+    score::{self, Score},
+    terminal::Terminal,
+};
+
+/// Outcome of typing the keys into Helix
+#[derive(Clone, Debug)]
+pub struct Checked {
+    /// The keys which were typed
+    pub keys: Vec<KeyEvent>,
+    /// Score of the keys
+    pub score: Score,
+    /// Score of the command of the example
+    pub published: Score,
+    /// The file after the keys were typed
+    pub result: String,
+    /// Whether the result is `## After`
+    pub solves: bool,
+}
+
+/// Type the `keys`, in the notation of the examples, into Helix of the `terminal`
+/// editing `## Before` of the `example`.
+///
+/// The keys are typed inside of the generated directory
+///
+/// # Errors
+///
+/// If the keys are invalid, Helix cannot be started, or it shows an error or a warning
+pub fn check(
+    example: &Example,
+    keys: &str,
+    paths: &Paths,
+    terminal: &Terminal,
+) -> miette::Result<Checked> {
+    let keys = parse_keys(keys, "keys")?;
+
+    let dir = paths.generated.join("check").join(&example.name);
+    fs::create_dir_all(&dir)
+        .map_err(|err| miette!("Failed to create `{}`: {err}", dir.display()))?;
+    generate_helix_config::generate(&dir)?;
+    let dir = dir
+        .canonicalize()
+        .map_err(|err| miette!("Failed to find `{}`: {err}", dir.display()))?;
+
+    let result = oracle::run(example, &keys, &dir, terminal)?;
+
+    Ok(Checked {
+        score: score::score(&keys),
+        published: example.score(),
+        solves: result.trim() == example.after.trim(),
+        result,
+        keys,
+    })
+}
+
+/// Type the keys of each `## Alternative` section of the `example` into Helix of the
+/// `terminal`, like [`check`]
+///
+/// # Errors
+///
+/// If the keys of an alternative do not produce `## After`, or they cannot be typed
+pub fn check_alternatives(
+    example: &Example,
+    paths: &Paths,
+    terminal: &Terminal,
+) -> miette::Result<()> {
+    for alternative in &example.alternatives {
+        let Checked { result, solves, .. } = check(example, &alternative.command, paths, terminal)?;
+        ensure!(
+            solves,
+            "alternative `{}` of example `{}` does not produce `## After`\n\n{}",
+            alternative.command,
+            example.name,
+            StrComparison::new(result.trim(), example.after.trim())
+        );
+    }
+    Ok(())
+}
+
+impl Example {
+    /// Markdown of this example, with the `keys` added in an `## Alternative` section at the end.
+    ///
+    /// The sections come after the list explaining the command, so they are not a part of it
+    pub fn with_alternative(&self, keys: &str) -> String {
+        let score = parse_keys(keys, &self.name)
+            .map(|keys| format!("\n\n_Score: {}_", score::score(&keys)))
+            .unwrap_or_default();
+
+        format!(
+            "{}\n\n## Alternative\n\n```\n{keys}\n```{score}\n",
+            self.contents.trim_end()
+        )
+    }
+}
@@ -0,0 +1,66 @@
+//! Browse the examples in the terminal
+
+use std::io::{self, Read as _, Write as _};
+
+use helix_golf::{
+    browse::Browser,
+    pty::{self, RawMode},
+    terminal::Size,
+};
+use miette::miette;
+use terminal_size::{Height, Width};
+
+use crate::command::{Config, Filter, validate};
+
+/// Show the examples included by the `filter` in the terminal, until the reader quits
+pub fn browse(config: &Config, filter: &Filter) -> miette::Result<()> {
+    let mut examples = validate::parse(config, filter)?;
+    examples.sort_by_key(|example| {
+        let score = example.score();
+        (score.get(config.sort), score.keystrokes)
+    });
+    let mut browser = Browser::new(examples);
+
+    let raw_mode = RawMode::enable()?;
+    let mut stdout = io::stdout();
+    let mut stdin = io::stdin();
+    // The alternate screen, without the cursor
+    let result = write(&mut stdout, b"\x1b[?1049h\x1b[?25l").and_then(|()| {
+        let mut buf = [0; 256];
+        while !browser.has_quit() {
+            let (cols, rows) = terminal_size::terminal_size().map_or_else(
+                || (Size::default().cols, Size::default().rows),
+                |(Width(cols), Height(rows))| (cols, rows),
+            );
+            write(
+                &mut stdout,
+                browser
+                    .render(usize::from(cols), usize::from(rows))
+                    .as_bytes(),
+            )?;
+
+            let len = stdin
+                .read(&mut buf)
+                .map_err(|err| miette!("Failed to read from the terminal: {err}"))?;
+            if len == 0 {
+                break;
+            }
+            for key in pty::decode(&[buf[..len].to_vec()]) {
+                browser.press(&key);
+            }
+        }
+        Ok(())
+    });
+    let restored = write(&mut stdout, b"\x1b[?25h\x1b[?1049l");
+    drop(raw_mode);
+
+    result.and(restored)
+}
+
+/// Write the `bytes` to the terminal
+fn write(stdout: &mut io::Stdout, bytes: &[u8]) -> miette::Result<()> {
+    stdout
+        .write_all(bytes)
+        .and_then(|()| stdout.flush())
+        .map_err(|err| miette!("Failed to write to the terminal: {err}"))
+}
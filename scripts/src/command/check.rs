@@ -0,0 +1,83 @@
+//! Check keys which someone proposes for an example
+
+use std::{cmp::Ordering, fs};
+
+use helix_golf::{
+    Example,
+    check::{self, Checked},
+    golf,
+    helix_version::Helix,
+    terminal::{Bounds, Size, Terminal},
+};
+use miette::{ensure, miette};
+
+use crate::command::{Config, ThemeKind, Verbosity};
+
+/// Type the `keys` into the `helix`, and print whether they produce `## After` of the
+/// `example` and how they score against its command.
+///
+/// If they do, and `write` is given, add them to the example as an alternative
+pub fn check(
+    config: &Config,
+    example: &Example,
+    keys: &str,
+    write: bool,
+    helix: Helix,
+    theme: ThemeKind,
+) -> miette::Result<()> {
+    let paths = &config.paths;
+    let name = &example.name;
+
+    let (size, _) = Size::fit(example, &Bounds::default());
+    let terminal = Terminal {
+        theme: theme.theme(),
+        size,
+        helix,
+    };
+
+    let Checked {
+        keys: key_events,
+        score,
+        published,
+        result,
+        solves,
+    } = check::check(example, keys, paths, &terminal)?;
+
+    let keys = golf::notation(&key_events);
+    println!(
+        "Command of example `{name}`: `{}` ({published})",
+        example.command
+    );
+    println!("Checked keys: `{keys}` ({score})");
+    match score.keystrokes.cmp(&published.keystrokes) {
+        Ordering::Less => println!(
+            "The keys are {} keystrokes shorter.",
+            published.keystrokes - score.keystrokes
+        ),
+        Ordering::Equal => println!("The keys are as long as the command."),
+        Ordering::Greater => println!(
+            "The keys are {} keystrokes longer.",
+            score.keystrokes - published.keystrokes
+        ),
+    }
+
+    ensure!(
+        solves,
+        "the keys do not produce `## After` of example `{name}`, they produce:\n{result}"
+    );
+    println!("The keys produce `## After`.");
+
+    if write {
+        let file = paths.root.join(format!("{name}.md"));
+        let markdown = example.with_alternative(&keys);
+        // The example must stay valid, so that validate checks the alternative
+        Example::from_markdown(markdown.clone(), &format!("{name}.md"), name)?;
+        fs::write(&file, markdown)
+            .map_err(|err| miette!("Failed to write `{}`: {err}", file.display()))?;
+        if config.verbosity >= Verbosity::Normal {
+            println!("Added the keys to `{}` as an alternative", file.display());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+//! Print a Helix config which binds the command of an example to a key
+
+use helix_golf::{Example, helix_version::Version, keymap::Keymap};
+use miette::{Severity, miette};
+
+use crate::command::{Config, Verbosity};
+
+/// Print the snippet of a Helix `config.toml` which binds the `key` to the command
+/// of the `example`, with the names of the commands of Helix `version`
+pub fn export_macro(
+    config: &Config,
+    example: &Example,
+    key: &str,
+    version: Option<Version>,
+) -> miette::Result<()> {
+    let version = version
+        .or_else(|| helix_golf::keymap::versions().into_iter().max())
+        .unwrap_or_default();
+    let exported = example.export_macro(&Keymap::of(version)?);
+
+    if !exported.prompts.is_empty() && config.verbosity >= Verbosity::Normal {
+        eprintln!(
+            "{:?}",
+            miette!(
+                severity = Severity::Warning,
+                help = "Check that the prompts match the files you use the macro on",
+                "the command of example `{}` types {} into prompts, which depends on \
+                the contents of `## Before`",
+                example.name,
+                exported
+                    .prompts
+                    .iter()
+                    .map(|prompt| format!("`{}`", prompt.text))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        );
+    }
+
+    print!("{}", exported.snippet(key, &example.title));
+
+    Ok(())
+}
@@ -1,80 +1,267 @@
-//! Generate demo `.mp4` files for each example
+//! Generate the demo of each example
 
-use miette::{IntoDiagnostic as _, ensure, miette};
 use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
-use std::{fs, process::Command};
+use std::{fs, iter, time::Instant};
 
-use crate::{command::GENERATED_DIR, parse_example::Example};
+use helix_golf::{
+    Asciicast, Example, Recorder, Svg, VerifyOnly, Vhs, check, determinism, failure,
+    helix_version::Helix,
+    manifest::{Entry, Manifest},
+    recorder,
+    report::{ExampleResult, Report},
+    snapshot::Accept,
+    terminal::{Bounds, Size, Terminal},
+    theme::Theme,
+};
+
+use miette::{bail, miette};
+
+use crate::command::{Config, RecorderKind, Verbosity};
+
+/// How the demos are generated
+pub struct Options {
+    /// Each demo is recorded with each of these
+    pub recorders: Vec<RecorderKind>,
+    /// Each demo is recorded in each of these
+    pub themes: Vec<Theme>,
+    /// The demos are recorded with the first one. Each example is tested
+    /// with the others as well, if they are not older than its minimum version
+    pub helixes: Vec<Helix>,
+    /// Limits of the terminal, which fits the contents of each example
+    pub bounds: Bounds,
+    /// Which snapshots are saved instead of compared with the previous run
+    pub accept: Accept,
+    /// Do not generate any demos, and play each example twice instead
+    pub determinism_check: bool,
+}
+
+/// Record the demo of each example as set by the `options`, and test it
+pub fn generate_demos(
+    config: &Config,
+    options: &Options,
+    examples: &[Example],
+) -> miette::Result<()> {
+    let paths = &config.paths;
+    let Options {
+        recorders,
+        themes,
+        helixes,
+        bounds,
+        accept,
+        determinism_check,
+    } = options;
+    let (accept, determinism_check) = (*accept, *determinism_check);
 
-/// Generate `.mp4` files for each command
-pub fn generate_demos(examples: &[Example]) -> miette::Result<()> {
     // Use a custom helix config to ensure reproducibility
     //
     // This is also necessary because VHS cannot handle some
-    crate::generate_helix_config::generate();
+    helix_golf::generate_helix_config::generate(&paths.generated)?;
+
+    let Some((primary, others)) = helixes.split_first() else {
+        bail!("at least one Helix is needed to generate the demos");
+    };
+    // The other versions of Helix are tested without recording demos, each in its own
+    // directory. Their snapshots are kept between runs like the ones of the primary version
+    let others = others
+        .iter()
+        .map(|helix| {
+            let helix_paths = paths.helix_version(&helix.version.to_string());
+            let generated = &helix_paths.generated;
+            fs::create_dir_all(generated)
+                .map_err(|err| miette!("Failed to create `{}`: {err}", generated.display()))?;
+            helix_golf::generate_helix_config::generate(generated)?;
+            Ok((helix, helix_paths))
+        })
+        .collect::<miette::Result<Vec<_>>>()?;
 
-    examples
+    let vhs = Vhs {
+        quiet: config.verbosity < Verbosity::Normal,
+    };
+    let recorders = recorders
+        .iter()
+        .map(|recorder| -> &dyn Recorder {
+            match recorder {
+                RecorderKind::Vhs => &vhs,
+                RecorderKind::Asciicast => &Asciicast,
+                RecorderKind::Svg => &Svg,
+                RecorderKind::Verify => &VerifyOnly,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let (results, errors): (Vec<_>, Vec<_>) = examples
         .par_iter()
-        .try_for_each(|example| -> Result<(), miette::Error> {
+        .map(|example| {
             let name = &example.name;
-            let ext = &example.language;
-
-            let tape_contents = example.to_string();
-
-            let tape_file = GENERATED_DIR.join(format!("{name}.tape"));
-
-            // Create .tape file
-            //
-            // These are the commands inputted into `vhs`
-            fs::write(&tape_file, tape_contents).map_err(|err| {
-                miette!(
-                    "Failed to create `{}` for example `{name}`: {err}",
-                    tape_file.display()
-                )
-            })?;
-
-            let modification_file = GENERATED_DIR.join(format!("{name}.{ext}"));
-
-            // First, this file has contents Before
-            //
-            // as we modify it, it'll have the contents that we expect from After
-            fs::write(&modification_file, &example.before).map_err(|err| {
-                miette!("Failed to create `Before` for example `{name}.{ext}`: {err}",)
-            })?;
-
-            ensure!(
-                which::which("vhs").is_ok(),
-                "ERROR (command `vhs` not found): You need to \
-                install `vhs` in order to generate the demos"
+
+            if config.verbosity >= Verbosity::Verbose {
+                println!("Recording example `{name}`");
+            }
+
+            let (size, warning) = Size::fit(example, bounds);
+            if let Some(warning) = warning
+                && config.verbosity >= Verbosity::Normal
+            {
+                eprintln!("{warning:?}");
+            }
+
+            let terminal = Terminal {
+                theme: themes[0],
+                size,
+                helix: primary.clone(),
+            };
+
+            let start = Instant::now();
+            let mut helix_versions = Vec::new();
+            let result = if determinism_check {
+                determinism::check(recorders[0], example, paths, &terminal)
+            } else if !example.supports(primary.version) {
+                Err(miette!(
+                    "example `{name}` needs Helix {} or newer, but is recorded with Helix {}",
+                    example.minimum_helix_version.unwrap_or_default(),
+                    primary.version
+                ))
+            } else {
+                recorders
+                    .iter()
+                    .try_for_each(|recorder| {
+                        // Without any demos, the theme does not matter
+                        let themes = if recorder.formats().is_empty() {
+                            &themes[..1]
+                        } else {
+                            themes
+                        };
+                        themes.iter().try_for_each(|&theme| {
+                            recorder::verify(
+                                *recorder,
+                                example,
+                                paths,
+                                &Terminal {
+                                    theme,
+                                    ..terminal.clone()
+                                },
+                                accept,
+                            )
+                        })
+                    })
+                    // The keys which were added with `check-keys --write` must keep working
+                    .and_then(|()| check::check_alternatives(example, paths, &terminal))
+            };
+            // Each Helix which the example failed with, to bundle the files of its run
+            let mut failed = Vec::new();
+            if result.is_ok() {
+                helix_versions.push(primary.version);
+            } else {
+                failed.push((paths, terminal.clone()));
+            }
+
+            // Also try the other versions when the example has failed,
+            // to know which ones it works with
+            let result = others
+                .iter()
+                .filter(|(helix, _)| !determinism_check && example.supports(helix.version))
+                .fold(result, |result, (helix, helix_paths)| {
+                    let terminal = Terminal {
+                        helix: (*helix).clone(),
+                        ..terminal.clone()
+                    };
+                    match recorder::verify(
+                        &VerifyOnly,
+                        example,
+                        helix_paths,
+                        &terminal,
+                        Accept::New,
+                    ) {
+                        Ok(()) => {
+                            helix_versions.push(helix.version);
+                            result
+                        }
+                        Err(err) => {
+                            failed.push((helix_paths, terminal));
+                            result.and(Err(miette!(
+                                "{err:?}\nwith Helix {} from `{}`",
+                                helix.version,
+                                helix.program.display()
+                            )))
+                        }
+                    }
+                });
+
+            if result.is_ok() && config.verbosity >= Verbosity::Normal {
+                println!("Example `{name}` has been successfully tested.");
+            }
+
+            let mut example_result = ExampleResult::new(
+                &paths.root,
+                name,
+                start.elapsed(),
+                Some(example.score()),
+                result.as_ref().err(),
             );
+            // Files of an earlier failure
+            for helix_paths in iter::once(paths).chain(others.iter().map(|(_, paths)| paths)) {
+                let _ = fs::remove_dir_all(example.failure_dir(helix_paths));
+            }
+            // The determinism check keeps the files of each run in their own directory.
+            // The report points at the bundle of the first Helix which the example failed with
+            if !determinism_check {
+                for (helix_paths, terminal) in &failed {
+                    match failure::bundle(example, helix_paths, terminal) {
+                        Ok(dir) => {
+                            example_result.bundle.get_or_insert(dir);
+                        }
+                        Err(err) => eprintln!("{err:?}"),
+                    }
+                }
+            }
+
+            example_result.helix_versions = helix_versions;
+
+            (example_result, result.err())
+        })
+        .unzip();
 
-            // Generate the .mp4 file preview
-            Command::new("vhs")
-                .arg(tape_file)
-                .spawn()
-                .into_diagnostic()?
-                .wait()
-                .into_diagnostic()?;
-
-            // Assert that the `## Before` code block is equal to the `## After` code block
-            // once we have executed the commands in `## Commands` code block.
-            pretty_assertions::assert_str_eq!(
-                fs::read_to_string(modification_file)
-                    .expect(
-                        "read to not fail, because file exists as \
-                        we have just written to it earlier"
-                    )
-                    .trim(),
-                example.after.trim(),
-                "example `{name}`"
+    for result in &results {
+        if let Some(bundle) = &result.bundle {
+            eprintln!(
+                "Files to reproduce example `{}` are in `{}`",
+                result.name,
+                bundle.display()
             );
+        }
+    }
 
-            println!("Example `{name}` has been successfully tested.");
+    // The demos of failed examples are embedded as well,
+    // so that it is possible to see what went wrong.
+    // The examples which were filtered out keep the demos of an earlier run
+    if !determinism_check {
+        let mut manifest = Manifest::read(&paths.generated).unwrap_or_default();
+        manifest.examples.extend(results.iter().map(|result| {
+            (
+                result.name.clone(),
+                Entry {
+                    formats: recorders
+                        .iter()
+                        .flat_map(|recorder| recorder.formats())
+                        .copied()
+                        .collect(),
+                    appearances: themes.iter().map(|theme| theme.appearance).collect(),
+                    helix: result.helix_versions.clone(),
+                },
+            )
+        }));
+        manifest.write(&paths.generated)?;
+    }
 
-            Ok(())
-        })?;
+    Report {
+        command: "generate-demos",
+        examples: results,
+    }
+    .finish(&paths.generated, errors.into_iter().flatten().collect())?;
 
-    println!("All examples have been successfully rendered and tested.");
+    if config.verbosity >= Verbosity::Normal {
+        println!("All examples have been successfully rendered and tested.");
+    }
 
     Ok(())
 }
@@ -0,0 +1,69 @@
+//! Search for a shorter command of an example
+
+use std::time::Duration;
+
+use helix_golf::{
+    Example,
+    golf::{self, Golfed},
+    helix_version::Helix,
+    terminal::{Bounds, Size, Terminal},
+};
+
+use crate::command::{Config, ThemeKind, Verbosity};
+
+/// Search for keys which are shorter than the command of the `example` for at most
+/// `budget`, typing them into the `helix`, and print each one which produces `## After`
+pub fn golf(
+    config: &Config,
+    example: &Example,
+    helix: Helix,
+    theme: ThemeKind,
+    budget: Duration,
+) -> miette::Result<()> {
+    let paths = &config.paths;
+    let name = &example.name;
+
+    let (size, _) = Size::fit(example, &Bounds::default());
+    let terminal = Terminal {
+        theme: theme.theme(),
+        size,
+        helix,
+    };
+
+    if config.verbosity >= Verbosity::Normal {
+        println!(
+            "Searching for a shorter command of example `{name}` for {}s",
+            budget.as_secs()
+        );
+    }
+
+    let Golfed {
+        score,
+        found,
+        tried,
+        out_of_time,
+    } = golf::golf(example, paths, &terminal, budget)?;
+
+    println!("Current command: `{}` ({score})", example.command);
+    if found.is_empty() {
+        println!("No shorter command found, after trying {tried} rewrites.");
+    } else {
+        println!("Shorter commands which produce `## After`:");
+        for rewrite in &found {
+            println!(
+                "  `{}` ({}), {}",
+                golf::notation(&rewrite.keys),
+                rewrite.score,
+                rewrite.description
+            );
+        }
+    }
+    if out_of_time && config.verbosity >= Verbosity::Normal {
+        println!(
+            "The time budget ran out after trying {tried} rewrites, \
+            give a larger `--budget` to search further."
+        );
+    }
+
+    Ok(())
+}
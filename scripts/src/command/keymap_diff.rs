@@ -0,0 +1,74 @@
+//! Find the examples which use keys that have changed between two releases of Helix
+
+use helix_golf::{
+    Example,
+    helix_version::Version,
+    keymap::{self, Keymap},
+};
+
+use crate::command::{Config, Verbosity};
+
+/// Print the keys which run a different command in Helix `new` than in Helix `old`,
+/// and where each of the `examples` uses them
+pub fn keymap_diff(
+    config: &Config,
+    old: Version,
+    new: Version,
+    examples: &[Example],
+) -> miette::Result<()> {
+    let changes = keymap::diff(&Keymap::of(old)?, &Keymap::of(new)?);
+
+    if changes.is_empty() {
+        if config.verbosity >= Verbosity::Normal {
+            println!("The keymap has not changed between Helix {old} and Helix {new}.");
+        }
+        return Ok(());
+    }
+
+    println!("Keys which changed between Helix {old} and Helix {new}:");
+    for change in &changes {
+        println!("  {change}");
+    }
+
+    let mut affected = 0;
+    for example in examples {
+        let uses = example
+            .key_sequences()
+            .into_iter()
+            .filter_map(|sequence| {
+                let change = keymap::find_change(&changes, sequence.mode, &sequence.keys)?;
+                let step = example
+                    .step_of(sequence.key_events.start)
+                    .map_or_else(String::new, |step| format!(", step {step}"));
+                Some(format!(
+                    "  {:?} mode `{}` (key {} of {}{step}): {} -> {}",
+                    sequence.mode,
+                    sequence.keys,
+                    sequence.key_events.start + 1,
+                    example.key_events.len(),
+                    change.old.as_deref().unwrap_or("nothing"),
+                    change.new.as_deref().unwrap_or("nothing"),
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        if !uses.is_empty() {
+            affected += 1;
+            println!("\nExample `{}` uses changed keys:", example.name);
+            for line in uses {
+                println!("{line}");
+            }
+        } else if config.verbosity >= Verbosity::Verbose {
+            println!("\nExample `{}` is not affected.", example.name);
+        }
+    }
+
+    if config.verbosity >= Verbosity::Normal {
+        println!(
+            "\n{affected} of {} examples need to be checked with Helix {new}.",
+            examples.len()
+        );
+    }
+
+    Ok(())
+}
@@ -1,36 +1,47 @@
-//! Preprocessor for mdbook that adds the example video to each page
+//! Preprocessor for mdbook that adds the demo of the example to each page
 
-use std::{env, io};
+use std::{
+    fmt::Write as _,
+    fs, io,
+    path::{Path, PathBuf},
+};
 
 use mdbook::preprocess::{CmdPreprocessor, Preprocessor};
 use miette::miette;
 use tap::Pipe as _;
 
-pub fn mdbook_preprocessor() -> miette::Result<()> {
-    // 1. Skip the binary name
-    // 2. Skip the first command `mdbook-preprocessor` which signals this binary to
-    // run the markdown preprocessor
-    match env::args().nth(2).as_deref() {
-        Some("supports") => {
-            // Supports all renderers
-            return Ok(());
-        }
-        Some(arg) => {
-            eprintln!("unknown argument: {arg}");
-            std::process::exit(1);
-        }
-        None => {}
-    }
+use helix_golf::{
+    Example, Format, Manifest, Paths,
+    export_macro::ExportedMacro,
+    helix_version::Version,
+    keymap::{self, Keymap},
+    manifest::Entry,
+    theme::Appearance,
+    translate,
+};
 
+/// Read the book from stdin, and write the modified book to stdout.
+///
+/// Generated files are in `out_dir`, by default `generated` in the book's source directory
+pub fn mdbook_preprocessor(out_dir: Option<&Path>) -> miette::Result<()> {
     CmdPreprocessor::parse_input(io::stdin())
         .map_err(|err| miette!("failed to parse mdbook input: {err}"))?
-        .pipe(|(ctx, book)| GolfPreprocessor.run(&ctx, book))
+        .pipe(|(ctx, book)| {
+            GolfPreprocessor {
+                out_dir: out_dir.map(Path::to_path_buf),
+            }
+            .run(&ctx, book)
+        })
         .map_err(|err| miette!("failed to run the helix-golf mdbook preprocessor: {err}"))?
         .pipe(|book| serde_json::to_writer(io::stdout(), &book))
         .map_err(|err| miette!("failed to write the modified mdbook: {err}"))
 }
 
-struct GolfPreprocessor;
+/// Adds the demo and the snapshots to each example
+struct GolfPreprocessor {
+    /// Where the generated files are, if not in the default location
+    out_dir: Option<PathBuf>,
+}
 
 impl Preprocessor for GolfPreprocessor {
     fn name(&self) -> &'static str {
@@ -39,39 +50,539 @@ impl Preprocessor for GolfPreprocessor {
 
     fn run(
         &self,
-        _ctx: &mdbook::preprocess::PreprocessorContext,
+        ctx: &mdbook::preprocess::PreprocessorContext,
         mut book: mdbook::book::Book,
     ) -> mdbook::errors::Result<mdbook::book::Book> {
+        let paths = Paths::new(
+            &ctx.root.join(&ctx.config.book.src),
+            self.out_dir.as_deref(),
+        );
+        let generated_url = paths
+            .generated_url()
+            .map_err(|err| mdbook::errors::Error::msg(format!("{err:?}")))?;
+        let manifest = Manifest::read(&paths.generated);
+
+        // Which demo to show when several formats have been generated, e.g.
+        //
+        // [preprocessor.helix-golf]
+        // demo = "cast"
+        let preferred = ctx
+            .config
+            .get_deserialized_opt::<Format, _>("preprocessor.helix-golf.demo")?;
+
+        // Rewrite the keys of each example into the keymap of the reader, e.g.
+        //
+        // [preprocessor.helix-golf]
+        // keymap = "helix-config.toml"
+        // keymap-version = "25.01"
+        let keymaps = ctx
+            .config
+            .get_deserialized_opt::<PathBuf, _>("preprocessor.helix-golf.keymap")?
+            .map(|config| {
+                let version = ctx
+                    .config
+                    .get_deserialized_opt::<Version, _>("preprocessor.helix-golf.keymap-version")?;
+                translate::keymaps(&ctx.root.join(config), version)
+                    .map_err(|err| mdbook::errors::Error::msg(format!("{err:?}")))
+            })
+            .transpose()?;
+
+        // Names of the commands in the snippets which bind the command of an example to a key
+        let macro_keymap = match &keymaps {
+            Some((_, reader)) => reader.clone(),
+            None => keymap::versions()
+                .into_iter()
+                .max()
+                .map(Keymap::of)
+                .transpose()
+                .map_err(|err| mdbook::errors::Error::msg(format!("{err:?}")))?
+                .ok_or_else(|| mdbook::errors::Error::msg("no keymap of Helix is known"))?,
+        };
+
+        let embed = |name: &str, autoplay: bool| {
+            Demo::new(&paths, &generated_url, manifest.as_ref(), preferred, name)
+                .map(|demo| demo.html(autoplay))
+        };
+
         book.for_each_mut(|book_item| {
-            if let mdbook::BookItem::Chapter(chapter) = book_item {
-                if let (Some(name), Some(start)) = (
-                    chapter
-                        .path
-                        .as_ref()
-                        .and_then(|path| path.file_stem())
-                        .and_then(|stem| stem.to_str()),
-                    chapter.content.find("## Command"),
-                ) {
-                    if name != "introduction" {
-                        let (before, after) = chapter.content.split_at(start);
-
-                        chapter.content = format!(
-                            r#"
-{before}
+            let mdbook::BookItem::Chapter(chapter) = book_item else {
+                return;
+            };
 
-## Preview
+            // The introduction has a marker for the demo of every example
+            while let Some(start) = chapter.content.find(DEMO_MARKER) {
+                let Some(len) = chapter.content[start..].find("-->") else {
+                    break;
+                };
+                let end = start + len + "-->".len();
+                let name = chapter.content[start + DEMO_MARKER.len()..end - "-->".len()]
+                    .trim()
+                    .to_string();
+                chapter
+                    .content
+                    .replace_range(start..end, &embed(&name, true).unwrap_or_default());
+            }
+
+            let Some(name) = chapter
+                .path
+                .as_ref()
+                .and_then(|path| path.file_stem())
+                .and_then(|stem| stem.to_str())
+                .filter(|name| *name != "introduction")
+            else {
+                return;
+            };
 
-<video controls>
-  <source src="generated/{name}.mp4" type="video/mp4">
-</video>
+            if let Some((default, reader)) = &keymaps
+                && let Ok(example) =
+                    Example::from_markdown(chapter.content.clone(), &format!("{name}.md"), name)
+            {
+                let translation = example.translate(default, reader);
+                for unbound in &translation.unbound {
+                    eprintln!(
+                        "warning: command `{}` of example `{name}` has no keys in {:?} mode \
+                        of the keymap, so its default keys `{}` are shown",
+                        unbound.command, unbound.mode, unbound.keys
+                    );
+                }
+                chapter.content = translation.markdown;
+            }
 
-{after}"#
-                        );
+            // Show the state of the buffer beneath each step of the explanation list.
+            //
+            // Insert from the last step, so the offsets of earlier steps stay valid
+            if let Ok(example) =
+                Example::from_markdown(chapter.content.clone(), &format!("{name}.md"), name)
+            {
+                for step in example.snapshot_steps().into_iter().rev() {
+                    let info = &example.steps[step - 1];
+                    if let Some(details) =
+                        snapshot_details(&paths, &generated_url, &example, step, info.indent)
+                    {
+                        chapter.content.insert_str(info.end_offset, &details);
                     }
                 }
+
+                chapter.content.insert_str(
+                    example.command_span.end,
+                    &format!(
+                        "\n\n_Score: {}_{}",
+                        example.score(),
+                        macro_details(&example.export_macro(&macro_keymap), &example.title)
+                    ),
+                );
+            }
+
+            if let Some(start) = chapter.content.find("## Command")
+                && let Some(demo) = embed(name, false)
+            {
+                let (before, after) = chapter.content.split_at(start);
+                let badge = tested_badge(&Manifest::entry(manifest.as_ref(), name).helix);
+
+                chapter.content = format!(
+                    "
+{before}
+
+## Preview
+
+{demo}
+
+{badge}
+
+{after}"
+                );
+            }
+        });
+
+        // The scripts are only loaded on pages which need them
+        book.for_each_mut(|book_item| {
+            let mdbook::BookItem::Chapter(chapter) = book_item else {
+                return;
+            };
+            if chapter.content.contains(CAST_CLASS) {
+                chapter.content.push_str(CAST_PLAYER);
+            }
+            if chapter.content.contains(KEYS_CLASS) {
+                chapter.content.push_str(KEYS_OVERLAY);
+            }
+            if chapter.content.contains(TESTED_CLASS) {
+                chapter.content.push_str(TESTED_STYLE);
+            }
+            if chapter.content.contains(theme_class(Appearance::Light)) {
+                chapter.content.push_str(THEME_STYLE);
             }
         });
 
         Ok(book)
     }
 }
+
+/// Placeholder for the demo of an example, followed by its name and `-->`
+pub const DEMO_MARKER: &str = "<!-- demo:";
+
+/// Class of the badge which lists the versions of Helix that an example works with
+const TESTED_CLASS: &str = "helix-golf-tested";
+
+/// Appearance of the badge
+const TESTED_STYLE: &str = r"
+
+<style>
+.helix-golf-tested { display: inline-block; padding: 0.1em 0.6em; border-radius: 1em; font-size: 0.85em; background: var(--quote-bg); border: 1px solid var(--quote-border); }
+</style>
+";
+
+/// Badge which lists the `versions` of Helix that an example works with.
+///
+/// Empty if the example has not been tested
+fn tested_badge(versions: &[Version]) -> String {
+    let mut versions = versions.to_vec();
+    versions.sort_unstable();
+    versions.dedup();
+
+    if versions.is_empty() {
+        return String::new();
+    }
+
+    format!(
+        "<span class=\"{TESTED_CLASS}\">tested with Helix {}</span>",
+        versions
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+/// Collapsible block beneath the command, with the snippet of a Helix config
+/// which binds the command to a key
+fn macro_details(exported: &ExportedMacro, title: &str) -> String {
+    let warning = if exported.prompts.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "The command types {} into prompts, so it only works on text like `## Before`.\n\n",
+            exported
+                .prompts
+                .iter()
+                .map(|prompt| format!("`{}`", prompt.text))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+
+    format!(
+        "\n\n<details>\n\
+        <summary>Bind this command to a key</summary>\n\n\
+        Add this to your Helix <code>config.toml</code>:\n\n\
+        ```toml\n{snippet}```\n\n\
+        {warning}\
+        </details>\n",
+        snippet = exported.snippet("F5", title),
+    )
+}
+
+/// Class of the element which is replaced by the player of an asciicast
+const CAST_CLASS: &str = "helix-golf-cast";
+
+/// Loads [asciinema-player](https://docs.asciinema.org/manual/player/),
+/// and creates a player for each asciicast on the page
+const CAST_PLAYER: &str = r#"
+
+<link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/asciinema-player@3.10.0/dist/bundle/asciinema-player.css">
+<script src="https://cdn.jsdelivr.net/npm/asciinema-player@3.10.0/dist/bundle/asciinema-player.min.js"></script>
+<script>
+for (const element of document.querySelectorAll(".helix-golf-cast")) {
+  const autoPlay = element.dataset.autoplay === "true";
+  element.player = AsciinemaPlayer.create(element.dataset.src, element, { autoPlay, loop: autoPlay, fit: "width" });
+}
+</script>
+"#;
+
+/// Class of the element which shows the keys of the demo before it
+const KEYS_CLASS: &str = "helix-golf-keys";
+
+/// Shows the keys of each demo, and highlights the key that was just pressed
+const KEYS_OVERLAY: &str = r#"
+
+<style>
+.helix-golf-keys { display: flex; flex-wrap: wrap; gap: 0.25em; margin: 0.5em 0; }
+.helix-golf-keys kbd { opacity: 0.5; }
+.helix-golf-keys kbd.current { opacity: 1; outline: 2px solid var(--links); }
+</style>
+<script>
+for (const overlay of document.querySelectorAll(".helix-golf-keys")) {
+  const demo = overlay.previousElementSibling;
+  fetch(overlay.dataset.src).then((response) => response.json()).then((keys) => {
+    const elements = keys.map(({ key }) => {
+      const kbd = document.createElement("kbd");
+      kbd.textContent = key;
+      overlay.append(kbd);
+      return kbd;
+    });
+    let current = -1;
+    setInterval(async () => {
+      const time = demo.player ? await demo.player.getCurrentTime() : demo.currentTime;
+      const index = keys.findLastIndex((key) => key.time <= time);
+      if (index !== current) {
+        elements[current]?.classList.remove("current");
+        elements[index]?.classList.add("current");
+        current = index;
+      }
+    }, 50);
+  });
+}
+</script>
+"#;
+
+/// Shows the demos and screenshots which match the theme of the book, when
+/// both dark and light variants have been generated
+const THEME_STYLE: &str = r"
+
+<style>
+.light .helix-golf-dark, .rust .helix-golf-dark { display: none; }
+.coal .helix-golf-light, .navy .helix-golf-light, .ayu .helix-golf-light { display: none; }
+</style>
+";
+
+/// Class of an element which is only shown if the book has a theme of the `appearance`
+const fn theme_class(appearance: Appearance) -> &'static str {
+    match appearance {
+        Appearance::Dark => "helix-golf-dark",
+        Appearance::Light => "helix-golf-light",
+    }
+}
+
+/// Demo of an example, as it is embedded into the book
+struct Demo<'a> {
+    /// URL of the generated directory, relative to the root of the book
+    generated_url: &'a str,
+    /// Name of the example
+    name: &'a str,
+    /// Which of the generated demos is shown
+    format: Format,
+    /// The demo has a variant for each of these
+    appearances: Vec<Appearance>,
+    /// Whether the example has captions, which only videos can show
+    captions: bool,
+    /// Whether the example has a timeline of keys, which images cannot be synchronized with
+    timeline: bool,
+}
+
+impl<'a> Demo<'a> {
+    /// Demo of example `name`, in the `preferred` format if it has been generated,
+    /// or otherwise in the first format listed in the `manifest`.
+    ///
+    /// `None` if the example has no demo
+    fn new(
+        paths: &Paths,
+        generated_url: &'a str,
+        manifest: Option<&Manifest>,
+        preferred: Option<Format>,
+        name: &'a str,
+    ) -> Option<Self> {
+        let Entry {
+            formats,
+            appearances,
+            ..
+        } = Manifest::entry(manifest, name);
+        let format = preferred
+            .filter(|preferred| formats.contains(preferred))
+            .or_else(|| formats.first().copied())?;
+
+        Some(Self {
+            generated_url,
+            name,
+            format,
+            appearances,
+            captions: paths.generated.join(format!("{name}.vtt")).exists(),
+            timeline: paths.generated.join(format!("{name}.keys.json")).exists(),
+        })
+    }
+
+    /// URL of the demo recorded with a theme of the `appearance`
+    fn src(&self, appearance: Appearance) -> String {
+        format!(
+            "{}/{}{}.{}",
+            self.generated_url,
+            self.name,
+            appearance.suffix(),
+            self.format.extension()
+        )
+    }
+
+    /// HTML elements which show the demo.
+    ///
+    /// With both a dark and a light variant, only the one matching the theme of the book is visible
+    fn html(&self, autoplay: bool) -> String {
+        // Images can choose the variant by themselves, which also works outside of the book
+        if self.format == Format::Svg {
+            let has = |appearance| self.appearances.contains(&appearance);
+
+            return if has(Appearance::Dark) && has(Appearance::Light) {
+                format!(
+                    "<picture>\n  \
+                    <source srcset=\"{light}\" media=\"(prefers-color-scheme: light)\">\n  \
+                    <img src=\"{dark}\" alt=\"Demo of the example\">\n\
+                    </picture>",
+                    light = self.src(Appearance::Light),
+                    dark = self.src(Appearance::Dark),
+                )
+            } else {
+                self.appearances
+                    .first()
+                    .map(|appearance| {
+                        format!(
+                            "<img src=\"{}\" alt=\"Demo of the example\">",
+                            self.src(*appearance)
+                        )
+                    })
+                    .unwrap_or_default()
+            };
+        }
+
+        match self.appearances.as_slice() {
+            [appearance] => self.variant(*appearance, autoplay),
+            appearances => appearances
+                .iter()
+                .map(|appearance| {
+                    format!(
+                        "<div class=\"{}\">\n{}\n</div>",
+                        theme_class(*appearance),
+                        self.variant(*appearance, autoplay)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+
+    /// Video or asciicast recorded with a theme of the `appearance`
+    fn variant(&self, appearance: Appearance, autoplay: bool) -> String {
+        let Self {
+            generated_url,
+            name,
+            captions,
+            timeline,
+            ..
+        } = *self;
+        let src = self.src(appearance);
+
+        let demo = if self.format == Format::Cast {
+            // The text of an asciicast can be selected and copied, unlike a video
+            format!(
+                "<div class=\"{CAST_CLASS}\" data-src=\"{src}\" data-autoplay=\"{autoplay}\"></div>"
+            )
+        } else {
+            format!(
+                "<video {}controls>\n  <source src=\"{src}\" type=\"video/mp4\">\n{}</video>",
+                if autoplay { "autoplay loop " } else { "" },
+                if captions {
+                    format!(
+                        "  <track kind=\"captions\" src=\"{generated_url}/{name}.vtt\" \
+                        srclang=\"en\" label=\"Steps\" default>\n"
+                    )
+                } else {
+                    String::new()
+                },
+            )
+        };
+
+        // Must directly follow the demo, to find it
+        if timeline {
+            format!(
+                "{demo}\n<div class=\"{KEYS_CLASS}\" data-src=\"{generated_url}/{name}.keys.json\"></div>"
+            )
+        } else {
+            demo
+        }
+    }
+}
+
+/// Collapsible block with the buffer after the `step`, placed inside of a list item
+/// whose contents start at the `indent` column.
+///
+/// `None` if the snapshot has not been captured yet.
+fn snapshot_details(
+    paths: &Paths,
+    generated_url: &str,
+    example: &Example,
+    step: usize,
+    indent: usize,
+) -> Option<String> {
+    let text = fs::read_to_string(
+        example
+            .snapshot_dir(paths)
+            .join(example.snapshot_file(step)),
+    )
+    .ok()?;
+
+    // The fence must be longer than any backticks inside of the snapshot
+    let fence = "`".repeat(
+        text.split(|ch| ch != '`')
+            .map(str::len)
+            .max()
+            .unwrap_or_default()
+            .max(2)
+            + 1,
+    );
+
+    let screenshots = [Appearance::Dark, Appearance::Light]
+        .into_iter()
+        .map(|appearance| (appearance, Example::screenshot_file(step, appearance)))
+        .filter(|(_, screenshot)| {
+            example
+                .generated_snapshot_dir(paths)
+                .join(screenshot)
+                .exists()
+        })
+        .collect::<Vec<_>>();
+    let url = |screenshot: &str| {
+        format!(
+            "{generated_url}/snapshots/{name}/{screenshot}",
+            name = example.name,
+        )
+    };
+    let selections = match screenshots.as_slice() {
+        [] => String::new(),
+        [(_, screenshot)] => format!("\n![Selections after step {step}]({})\n", url(screenshot)),
+        screenshots => {
+            screenshots
+                .iter()
+                .fold(String::from("\n"), |mut out, (appearance, screenshot)| {
+                    let _ = writeln!(
+                        out,
+                        "<img class=\"{}\" src=\"{}\" alt=\"Selections after step {step}\">",
+                        theme_class(*appearance),
+                        url(screenshot)
+                    );
+                    out
+                })
+        }
+    };
+
+    let details = format!(
+        "<details>\n\
+        <summary>State after this step</summary>\n\n\
+        {fence}{lang}\n{text}\n{fence}\n\
+        {selections}\n\
+        </details>",
+        lang = example.language,
+        text = text.trim_end_matches('\n'),
+    );
+
+    let indent = " ".repeat(indent);
+
+    details
+        .lines()
+        .fold(String::from("\n"), |mut out, line| {
+            out.push('\n');
+            if !line.is_empty() {
+                out.push_str(&indent);
+                out.push_str(line);
+            }
+            out
+        })
+        // the list item after this one must not be a part of the `<details>` HTML block
+        .pipe(|details| format!("{details}\n"))
+        .pipe(Some)
+}
@@ -1,58 +1,541 @@
 //! The `Command` represents possible things this binary can do
 
+mod browse;
+mod check;
+mod export_macro;
 mod generate_demos;
+mod golf;
+mod keymap_diff;
 mod mdbook_preprocessor;
+mod practice;
+mod translate;
 mod validate;
 
-use std::str::FromStr;
-use std::{
-    path::{Path, PathBuf},
-    sync::LazyLock,
-};
+use std::{path::PathBuf, time::Duration};
 
-use tap::Pipe as _;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use helix_golf::{
+    Example, Paths,
+    helix_version::{Helix, Version},
+    score::Metric,
+    snapshot::Accept,
+    terminal::Bounds,
+    theme::{self, Theme},
+};
+use miette::{ensure, miette};
 
-/// Source directory for the mdbook content files
-pub static ROOT_DIR: LazyLock<PathBuf> =
-    LazyLock::new(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join("src"));
+/// Scripts for Helix Golf
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// The action to perform
+    #[command(subcommand)]
+    command: Command,
+    /// Options shared by all commands
+    #[command(flatten)]
+    options: Options,
+}
 
-/// Directory where we place all of the generated files
-pub static GENERATED_DIR: LazyLock<PathBuf> = LazyLock::new(|| ROOT_DIR.join("generated"));
+/// Options shared by all commands
+#[derive(Args)]
+struct Options {
+    /// Directory which contains the markdown file of each example
+    #[arg(long, global = true, default_value = "src")]
+    root: PathBuf,
+    /// Directory where all of the generated files are placed [default: <ROOT>/generated]
+    #[arg(long, global = true)]
+    out_dir: Option<PathBuf>,
+    /// Amount of examples to process in parallel [default: amount of CPUs]
+    #[arg(long, short, global = true)]
+    jobs: Option<usize>,
+    /// Only print errors
+    #[arg(long, short, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Print more details about each example
+    #[arg(long, short, global = true)]
+    verbose: bool,
+    /// Metric of the score which the examples are sorted by, in the book
+    #[arg(long, global = true, value_enum, default_value = "keystrokes")]
+    sort: SortKind,
+}
 
 /// The action that the binary should execute
-#[derive(Clone, Copy)]
-pub enum Command {
+#[derive(Subcommand)]
+enum Command {
     /// Parse all examples, to make sure they conform to the required structure
-    Validate,
+    Validate(Filter),
     /// 1. Perform `Validate`
-    /// 2. Generate demo `.mp4` files
+    /// 2. Generate the demo of each example
     /// 3. Test that each demo is correct
-    GenerateDemos,
-    /// Transforms each markdown file, adding a `<video>` element for demo
-    MdBookPreprocessor,
+    GenerateDemos {
+        /// Examples to generate
+        #[command(flatten)]
+        filter: Filter,
+        /// How to record the demos. Can be given several times to record each format
+        #[arg(long, value_enum, default_value = "vhs")]
+        recorder: Vec<RecorderKind>,
+        /// Theme of the terminal. Give a dark and a light theme to show
+        /// the one that matches the theme of the reader
+        #[arg(long, value_enum, default_value = "catppuccin-mocha")]
+        theme: Vec<ThemeKind>,
+        /// Helix to play the examples with. Can be given several times to test each
+        /// example with several versions, the demos are recorded with the first one
+        #[arg(long, default_value = "hx")]
+        helix: Vec<PathBuf>,
+        /// Limits of the size of the terminal, which fits the contents of each example
+        #[command(flatten)]
+        bounds: TerminalBounds,
+        /// Save the snapshots and final screens which differ from the previous run,
+        /// instead of failing
+        #[arg(long)]
+        accept: bool,
+        /// Fail if a snapshot or final screen has not been saved before, instead of
+        /// saving it. Used in CI, where the saved snapshots must be committed
+        #[arg(long, conflicts_with = "accept")]
+        require_snapshots: bool,
+        /// Instead of generating the demos, play each example twice with the first
+        /// recorder and theme, and fail if the results differ
+        #[arg(long, conflicts_with = "accept")]
+        determinism_check: bool,
+    },
+    /// Show which keys of the default keymap changed between two releases of Helix,
+    /// and which examples use them
+    KeymapDiff {
+        /// Release of Helix that the examples work with, e.g. `24.07`
+        old: Version,
+        /// Release of Helix to upgrade to, e.g. `25.01`
+        new: Version,
+        /// Examples to check
+        #[command(flatten)]
+        filter: Filter,
+    },
+    /// Rewrite the keys of each example into the keys of a custom Helix keymap
+    Translate {
+        /// Helix `config.toml` whose `[keys]` remap the default keymap
+        #[arg(long)]
+        config: PathBuf,
+        /// Release of Helix whose default keymap the examples are written for
+        /// [default: the latest release whose keymap is known]
+        #[arg(long)]
+        helix_version: Option<Version>,
+        /// Directory to write the translated examples into, instead of printing them
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Examples to translate
+        #[command(flatten)]
+        filter: Filter,
+    },
+    /// Print a snippet of a Helix `config.toml` which binds the command of an example to a key
+    ExportMacro {
+        /// Name of the example, i.e. the markdown file name without `.md`
+        example: String,
+        /// Key of normal mode to bind, in the notation of the Helix config
+        #[arg(long, default_value = "F5")]
+        key: String,
+        /// Release of Helix whose command names are used
+        /// [default: the latest release whose keymap is known]
+        #[arg(long)]
+        helix_version: Option<Version>,
+    },
+    /// Search for keys which are shorter than the command of an example, and
+    /// still produce `## After` when typed into Helix
+    Golf {
+        /// Name of the example, i.e. the markdown file name without `.md`
+        example: String,
+        /// Stop searching after this many seconds
+        #[arg(long, default_value_t = 60)]
+        budget: u64,
+        /// Helix to type the keys into
+        #[arg(long, default_value = "hx")]
+        helix: PathBuf,
+        /// Theme of the terminal
+        #[arg(long, value_enum, default_value = "catppuccin-mocha")]
+        theme: ThemeKind,
+    },
+    /// Browse the examples in the terminal, with a fuzzy search by title and tags
+    Browse(Filter),
+    /// Edit `## Before` of an example in Helix, then check that it became `## After`
+    /// and compare the keystrokes with the command of the example
+    Practice {
+        /// Name of the example, i.e. the markdown file name without `.md`.
+        /// A random example if not given
+        example: Option<String>,
+        /// Show the command of the example and how it works afterwards
+        #[arg(long)]
+        reveal: bool,
+        /// Helix to practise in
+        #[arg(long, default_value = "hx")]
+        helix: PathBuf,
+    },
+    /// Type keys, such as a shorter command that someone proposes, into Helix and check
+    /// that they turn `## Before` of an example into `## After`
+    Check {
+        /// Name of the example, i.e. the markdown file name without `.md`
+        example: String,
+        /// The keys, in the notation of the examples such as `%<alt-s>ms"`
+        keys: String,
+        /// Add the keys to the example as an alternative, if they produce `## After`
+        #[arg(long)]
+        write: bool,
+        /// Helix to type the keys into
+        #[arg(long, default_value = "hx")]
+        helix: PathBuf,
+        /// Theme of the terminal
+        #[arg(long, value_enum, default_value = "catppuccin-mocha")]
+        theme: ThemeKind,
+    },
+    /// Transforms each markdown file, embedding the demo
+    MdbookPreprocessor {
+        /// Asked by mdbook, to check if the renderer is supported
+        #[command(subcommand)]
+        supports: Option<Supports>,
+    },
 }
 
-impl Command {
-    pub const ERROR: &str = "Expected either `validate`, `generate-demos` or `mdbook-preprocessor` as the first argument";
+/// Check if the preprocessor supports a renderer
+#[derive(Subcommand)]
+enum Supports {
+    /// Check if the preprocessor supports the renderer
+    Supports {
+        /// Name of the renderer
+        renderer: String,
+    },
+}
 
-    pub fn execute(self) -> miette::Result<()> {
+/// Selects which examples to process.
+///
+/// When no filter is given, all examples are included.
+/// Otherwise, an example is included if it matches all of the filters.
+#[derive(Args, Default)]
+pub struct Filter {
+    /// Names of the examples to include, i.e. the markdown file name without `.md`
+    examples: Vec<String>,
+    /// Include examples whose name matches this glob pattern, e.g. `invert_*`
+    #[arg(long)]
+    glob: Vec<String>,
+    /// Include examples which have this tag
+    #[arg(long)]
+    tag: Vec<String>,
+}
+
+impl Filter {
+    /// Whether every example is included
+    pub const fn is_empty(&self) -> bool {
+        self.examples.is_empty() && self.glob.is_empty() && self.tag.is_empty()
+    }
+
+    /// Whether an example with this `name` should be included.
+    ///
+    /// Does not check the tags, as they are known only once the example is parsed
+    pub fn includes_name(&self, name: &str) -> miette::Result<bool> {
+        let patterns = self
+            .glob
+            .iter()
+            .map(|pattern| {
+                glob::Pattern::new(pattern)
+                    .map_err(|err| miette!("invalid glob pattern `{pattern}`: {err}"))
+            })
+            .collect::<miette::Result<Vec<_>>>()?;
+
+        Ok(
+            (self.examples.is_empty() || self.examples.iter().any(|example| example == name))
+                && (patterns.is_empty() || patterns.iter().any(|pattern| pattern.matches(name))),
+        )
+    }
+
+    /// Whether the `example` has any of the tags in this filter
+    pub fn includes_tags(&self, example: &Example) -> bool {
+        self.tag.is_empty() || self.tag.iter().any(|tag| example.tags.contains(tag))
+    }
+}
+
+/// Parse the example with this `name`
+fn find_example(config: &Config, name: &str) -> miette::Result<Example> {
+    let filter = Filter {
+        examples: vec![name.to_string()],
+        glob: Vec::new(),
+        tag: Vec::new(),
+    };
+    validate::parse(config, &filter)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| miette!("there is no example `{name}`"))
+}
+
+/// Limits of the size of the terminal, see [`helix_golf::terminal`]
+#[derive(Args)]
+struct TerminalBounds {
+    /// Width of the demos, in pixels
+    #[arg(long, default_value_t = Bounds::default().width)]
+    width: u32,
+    /// Smallest font size, used for examples with long lines
+    #[arg(long, default_value_t = Bounds::default().min_font_size)]
+    min_font_size: u32,
+    /// Largest font size, used for examples with short lines
+    #[arg(long, default_value_t = Bounds::default().max_font_size)]
+    max_font_size: u32,
+    /// Smallest height of the demos, in pixels
+    #[arg(long, default_value_t = Bounds::default().min_height)]
+    min_height: u32,
+    /// Largest height of the demos, in pixels
+    #[arg(long, default_value_t = Bounds::default().max_height)]
+    max_height: u32,
+}
+
+impl TryFrom<TerminalBounds> for Bounds {
+    type Error = miette::Report;
+
+    fn try_from(bounds: TerminalBounds) -> miette::Result<Self> {
+        ensure!(
+            bounds.min_font_size <= bounds.max_font_size,
+            "`--min-font-size` {} is larger than `--max-font-size` {}",
+            bounds.min_font_size,
+            bounds.max_font_size
+        );
+        ensure!(
+            bounds.min_height <= bounds.max_height,
+            "`--min-height` {} is larger than `--max-height` {}",
+            bounds.min_height,
+            bounds.max_height
+        );
+        ensure!(bounds.width > 0, "`--width` must be larger than 0");
+
+        Ok(Self {
+            width: bounds.width,
+            min_font_size: bounds.min_font_size,
+            max_font_size: bounds.max_font_size,
+            min_height: bounds.min_height,
+            max_height: bounds.max_height,
+        })
+    }
+}
+
+/// Available recorders, see [`helix_golf::recorder`]
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RecorderKind {
+    /// `.mp4` video recorded with `vhs`
+    Vhs,
+    /// `.cast` terminal recording, played in the browser
+    Asciicast,
+    /// Animated `.svg`, rendered without any external programs
+    Svg,
+    /// Only test the examples, without recording them
+    Verify,
+}
+
+/// Available themes, see [`helix_golf::theme`]
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ThemeKind {
+    /// Dark
+    CatppuccinMocha,
+    /// Light
+    CatppuccinLatte,
+}
+
+impl ThemeKind {
+    /// Colors of the theme
+    pub const fn theme(self) -> Theme {
+        match self {
+            Self::CatppuccinMocha => theme::CATPPUCCIN_MOCHA,
+            Self::CatppuccinLatte => theme::CATPPUCCIN_LATTE,
+        }
+    }
+}
+
+/// Metrics that the examples can be sorted by, see [`helix_golf::score`]
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SortKind {
+    /// Every key that is pressed
+    Keystrokes,
+    /// Commands, with the keys that they wait for, such as the text of a prompt
+    Commands,
+    /// Characters typed in insert mode
+    InsertedCharacters,
+}
+
+impl SortKind {
+    /// The metric of the score
+    pub const fn metric(self) -> Metric {
         match self {
-            Self::Validate => validate::validate().map(drop),
-            Self::GenerateDemos => validate::validate()?.pipe_deref(generate_demos::generate_demos),
-            Self::MdBookPreprocessor => mdbook_preprocessor::mdbook_preprocessor(),
+            Self::Keystrokes => Metric::Keystrokes,
+            Self::Commands => Metric::Commands,
+            Self::InsertedCharacters => Metric::InsertedCharacters,
         }
     }
 }
 
-impl FromStr for Command {
-    type Err = &'static str;
+/// How much to print
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Verbosity {
+    /// Only errors
+    Quiet,
+    /// Progress of each example
+    Normal,
+    /// Everything
+    Verbose,
+}
+
+/// Everything that the commands need to know
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Where to read from and write to
+    pub paths: Paths,
+    /// How much to print
+    pub verbosity: Verbosity,
+    /// Metric of the score which the examples are sorted by
+    pub sort: Metric,
+}
+
+impl Cli {
+    /// Run the command
+    pub fn execute(self) -> miette::Result<()> {
+        let Options {
+            root,
+            out_dir,
+            jobs,
+            quiet,
+            verbose,
+            sort,
+        } = self.options;
+
+        if let Some(jobs) = jobs {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(jobs)
+                .build_global()
+                .map_err(|err| miette!("failed to use {jobs} jobs: {err}"))?;
+        }
+
+        let config = Config {
+            paths: Paths::new(&root, out_dir.as_deref()),
+            verbosity: if quiet {
+                Verbosity::Quiet
+            } else if verbose {
+                Verbosity::Verbose
+            } else {
+                Verbosity::Normal
+            },
+            sort: sort.metric(),
+        };
+
+        match self.command {
+            Command::Validate(filter) => validate::validate(&config, &filter).map(drop),
+            Command::GenerateDemos {
+                filter,
+                recorder,
+                theme,
+                helix,
+                bounds,
+                accept,
+                require_snapshots,
+                determinism_check,
+            } => {
+                let themes = theme.into_iter().map(ThemeKind::theme).collect::<Vec<_>>();
+                for (i, theme) in themes.iter().enumerate() {
+                    ensure!(
+                        themes[..i]
+                            .iter()
+                            .all(|other| other.appearance != theme.appearance),
+                        "only one dark and one light theme can be used, \
+                        but there are several {} themes",
+                        format!("{:?}", theme.appearance).to_lowercase()
+                    );
+                }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "validate" => Ok(Self::Validate),
-            "generate-demos" => Ok(Self::GenerateDemos),
-            "mdbook-preprocessor" => Ok(Self::MdBookPreprocessor),
-            _ => Err("Expected either `validate` or `generate-demos` as the first argument"),
+                generate_demos::generate_demos(
+                    &config,
+                    &generate_demos::Options {
+                        recorders: recorder,
+                        themes,
+                        helixes: helix
+                            .iter()
+                            .map(|program| Helix::new(program))
+                            .collect::<miette::Result<_>>()?,
+                        bounds: bounds.try_into()?,
+                        accept: if accept {
+                            Accept::All
+                        } else if require_snapshots {
+                            Accept::Nothing
+                        } else {
+                            Accept::New
+                        },
+                        determinism_check,
+                    },
+                    &validate::validate(&config, &filter)?,
+                )
+            }
+            Command::KeymapDiff { old, new, filter } => {
+                keymap_diff::keymap_diff(&config, old, new, &validate::parse(&config, &filter)?)
+            }
+            Command::Translate {
+                config: helix_config,
+                helix_version,
+                output,
+                filter,
+            } => translate::translate(
+                &config,
+                &helix_config,
+                helix_version,
+                output.as_deref(),
+                &validate::parse(&config, &filter)?,
+            ),
+            Command::ExportMacro {
+                example,
+                key,
+                helix_version,
+            } => export_macro::export_macro(
+                &config,
+                &find_example(&config, &example)?,
+                &key,
+                helix_version,
+            ),
+            Command::Golf {
+                example,
+                budget,
+                helix,
+                theme,
+            } => golf::golf(
+                &config,
+                &find_example(&config, &example)?,
+                Helix::new(&helix)?,
+                theme,
+                Duration::from_secs(budget),
+            ),
+            Command::Browse(filter) => browse::browse(&config, &filter),
+            Command::Practice {
+                example,
+                reveal,
+                helix,
+            } => practice::practice(
+                &config,
+                example
+                    .map(|example| find_example(&config, &example))
+                    .transpose()?,
+                &Helix::new(&helix)?,
+                reveal,
+            ),
+            Command::Check {
+                example,
+                keys,
+                write,
+                helix,
+                theme,
+            } => check::check(
+                &config,
+                &find_example(&config, &example)?,
+                &keys,
+                write,
+                Helix::new(&helix)?,
+                theme,
+            ),
+            Command::MdbookPreprocessor {
+                supports: Some(Supports::Supports { .. }),
+            } => {
+                // Supports all renderers
+                Ok(())
+            }
+            Command::MdbookPreprocessor { supports: None } => {
+                mdbook_preprocessor::mdbook_preprocessor(out_dir.as_deref())
+            }
         }
     }
 }
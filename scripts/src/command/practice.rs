@@ -0,0 +1,95 @@
+//! Practise an example in Helix
+
+use std::{
+    io::{self, BufRead as _},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use helix_golf::{
+    Example, golf,
+    helix_version::Helix,
+    practice::{self, Attempt},
+};
+use miette::{ensure, miette};
+use pretty_assertions::StrComparison;
+
+use crate::command::{Config, Filter, validate};
+
+/// Let the reader edit `## Before` of the `example` in the `helix`, or of a random
+/// example if there is none. Then print whether it became `## After`, and how many
+/// keystrokes it took.
+///
+/// If `reveal` is given, print the command of the example and how it works afterwards
+pub fn practice(
+    config: &Config,
+    example: Option<Example>,
+    helix: &Helix,
+    reveal: bool,
+) -> miette::Result<()> {
+    let example = example.map_or_else(|| random_example(config), Ok)?;
+
+    println!("# {}\n", example.title);
+    if let Some(description) = &example.description {
+        println!("{description}\n");
+    }
+    println!("Turn this:\n\n{}\n", example.before.trim_end());
+    println!("Into this:\n\n{}\n", example.after.trim_end());
+    println!(
+        "The command of the example takes {} keystrokes. Save with `:write` \
+        and quit with `:quit`, or both with `:wq`, once you are done.",
+        example.score().keystrokes
+    );
+    println!("Press enter to start.");
+    io::stdin()
+        .lock()
+        .read_line(&mut String::new())
+        .map_err(|err| miette!("Failed to read from the terminal: {err}"))?;
+
+    let Attempt {
+        keys,
+        score,
+        reference,
+        result,
+        solves,
+    } = practice::practise(&example, helix)?;
+
+    if solves {
+        println!("Solved!");
+    } else {
+        println!(
+            "The file is not `## After` yet:\n\n{}\n",
+            StrComparison::new(result.trim(), example.after.trim())
+        );
+    }
+    println!("Your keys: `{}` ({score})", golf::notation(&keys));
+    println!("The command of the example scores {reference}");
+    if solves && score.keystrokes < reference.keystrokes {
+        println!(
+            "Your keys are shorter! Propose them with \
+            `cargo check-keys {} '<keys>'`",
+            example.name
+        );
+    }
+
+    if reveal {
+        println!("\n## Command\n\n{}\n", example.command);
+        for (i, step) in example.steps.iter().enumerate() {
+            println!("{}. {}", i + 1, step.text.trim());
+        }
+    }
+
+    Ok(())
+}
+
+/// Any of the examples
+fn random_example(config: &Config) -> miette::Result<Example> {
+    let mut examples = validate::parse(config, &Filter::default())?;
+    ensure!(!examples.is_empty(), "there are no examples to practise");
+    // Random enough to pick an example
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.subsec_nanos())
+        .unwrap_or_default();
+    let i = usize::try_from(seed).unwrap_or_default() % examples.len();
+    Ok(examples.swap_remove(i))
+}
@@ -0,0 +1,63 @@
+//! Rewrite the examples into the keys of a custom Helix keymap
+
+use std::{fs, path::Path};
+
+use helix_golf::{Example, helix_version::Version, translate};
+use miette::{Severity, miette};
+
+use crate::command::{Config, Verbosity};
+
+/// Translate each of the `examples` into the keymap of the Helix `helix_config`, whose
+/// default keys are those of Helix `version`.
+///
+/// Each translated example is written into `output` as `<name>.md`, or printed if there is no `output`
+pub fn translate(
+    config: &Config,
+    helix_config: &Path,
+    version: Option<Version>,
+    output: Option<&Path>,
+    examples: &[Example],
+) -> miette::Result<()> {
+    let (default, reader) = translate::keymaps(helix_config, version)?;
+
+    if let Some(output) = output {
+        fs::create_dir_all(output)
+            .map_err(|err| miette!("Failed to create `{}`: {err}", output.display()))?;
+    }
+
+    for example in examples {
+        let name = &example.name;
+        let translation = example.translate(&default, &reader);
+
+        if config.verbosity >= Verbosity::Normal {
+            for unbound in &translation.unbound {
+                eprintln!(
+                    "{:?}",
+                    miette!(
+                        severity = Severity::Warning,
+                        help = "Bind the command to a key in your config, or use the default keys",
+                        "command `{}` of example `{name}` has no keys in {:?} mode of your keymap, \
+                        so its default keys `{}` are kept",
+                        unbound.command,
+                        unbound.mode,
+                        unbound.keys,
+                    )
+                );
+            }
+        }
+
+        match output {
+            Some(output) => {
+                let file = output.join(format!("{name}.md"));
+                fs::write(&file, &translation.markdown)
+                    .map_err(|err| miette!("Failed to write `{}`: {err}", file.display()))?;
+                if config.verbosity >= Verbosity::Verbose {
+                    println!("Translated example `{name}` into `{}`", file.display());
+                }
+            }
+            None => println!("{}", translation.markdown),
+        }
+    }
+
+    Ok(())
+}
@@ -1,34 +1,140 @@
 //! Validate that all of the examples adhere to a certain structure
 
-use std::{collections::HashSet, env, fmt::Write as _, fs};
+use std::{fmt::Write as _, fs, time::Instant};
 
-use crate::{
-    command::{GENERATED_DIR, ROOT_DIR},
-    parse_example::Example,
+use helix_golf::{
+    Example,
+    report::{ExampleResult, Report},
 };
+
+use crate::command::{Config, Filter, Verbosity, mdbook_preprocessor::DEMO_MARKER};
 use miette::miette;
 use tap::Pipe as _;
 
-/// Make sure each example has the required structure
-pub fn validate() -> miette::Result<Vec<Example>> {
-    // If user passes any examples, those will be the only ones that are included.
-    //
-    // If no examples are passed, then include everything
-    let only_include_these_examples: HashSet<_> = env::args()
-        // 1. skip binary name
-        // 2. skip argument type
-        .skip(2)
-        .collect();
+/// Parse each example included by the `filter`, sorted by name.
+///
+/// Unlike [`validate`], it does not write any files, so the generated files stay as they are
+pub fn parse(config: &Config, filter: &Filter) -> miette::Result<Vec<Example>> {
+    let mut examples = Vec::new();
+    for path in Example::paths(&config.paths.root)? {
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default();
+        if !filter.includes_name(name)? {
+            continue;
+        }
+        let example = Example::parse(&path)?;
+        if filter.includes_tags(&example) {
+            examples.push(example);
+        }
+    }
+    examples.sort_by(|a, b| a.name.cmp(&b.name));
 
-    fs::create_dir_all(&*GENERATED_DIR)
-        .and_then(|()| fs::remove_dir_all(&*GENERATED_DIR))
-        .and_then(|()| fs::create_dir_all(&*GENERATED_DIR))
-        .map_err(|err| miette!("failed cleaning the generated directory: {err}"))?;
+    Ok(examples)
+}
 
-    let mut examples = Example::parse_all(&ROOT_DIR, &only_include_these_examples)?;
+/// Make sure each example included by the `filter` has the required structure
+pub fn validate(config: &Config, filter: &Filter) -> miette::Result<Vec<Example>> {
+    let paths = &config.paths;
+
+    let mut examples = Vec::new();
+    let mut results = Vec::new();
+    let mut errors = Vec::new();
+
+    for path in Example::paths(&paths.root)? {
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default()
+            .to_string();
+
+        if !filter.includes_name(&name)? {
+            continue;
+        }
+
+        let start = Instant::now();
+        let example = Example::parse(&path);
+        let duration = start.elapsed();
+
+        match example {
+            Ok(example) => {
+                if !filter.includes_tags(&example) {
+                    continue;
+                }
+
+                if config.verbosity >= Verbosity::Verbose {
+                    println!(
+                        "Example `{name}` is valid ({}, took {duration:?})",
+                        example.score()
+                    );
+                }
+                if config.verbosity >= Verbosity::Normal {
+                    for lint in example.lints() {
+                        eprintln!("{:?}", miette::Report::new(example.lint_warning(&lint)));
+                    }
+                }
+
+                results.push(ExampleResult::new(
+                    &paths.root,
+                    &name,
+                    duration,
+                    Some(example.score()),
+                    None,
+                ));
+                examples.push(example);
+            }
+            Err(err) => {
+                results.push(ExampleResult::new(
+                    &paths.root,
+                    &name,
+                    duration,
+                    None,
+                    Some(&err),
+                ));
+                errors.push(err);
+            }
+        }
+    }
+
+    // Without a filter every example is included, and the files of removed examples go too
+    let names = results
+        .iter()
+        .map(|result| result.name.clone())
+        .collect::<Vec<_>>();
+    paths.clean_generated((!filter.is_empty()).then_some(names.as_slice()))?;
+
+    Report {
+        command: "validate",
+        examples: results,
+    }
+    .finish(&paths.generated, errors)?;
+
+    // The index of the book lists every example, not only the ones of a filtered run
+    if filter.is_empty() {
+        write_index(config, &examples)?;
+    } else {
+        write_index(config, &parse(config, &Filter::default())?)?;
+    }
+
+    // We want to sort examples from the smallest score to the largest
+    examples.sort_by_key(|example| {
+        let score = example.score();
+        (score.get(config.sort), score.keystrokes)
+    });
 
-    // We want to sort examples from smallest command count to largest
-    examples.sort_by(|a, b| a.key_events.len().cmp(&b.key_events.len()));
+    Ok(examples)
+}
+
+/// Write `SUMMARY.md` and `introduction.md` of the book, which list the `examples`
+/// from the smallest score to the largest
+fn write_index(config: &Config, examples: &[Example]) -> miette::Result<()> {
+    let paths = &config.paths;
+    let mut examples = examples.iter().collect::<Vec<_>>();
+    examples.sort_by_key(|example| {
+        let score = example.score();
+        (score.get(config.sort), score.keystrokes)
+    });
 
     examples
         .iter()
@@ -67,10 +173,11 @@ pub fn validate() -> miette::Result<Vec<Example>> {
 
 {desc}
 
-<video autoplay controls loop>
-  <source src=\"generated/{name}.mp4\">
-</video>\n\n",
-                    desc = example.description.as_deref().unwrap_or("")
+_Score: {score}_
+
+{DEMO_MARKER} {name} -->\n\n",
+                    desc = example.description.as_deref().unwrap_or(""),
+                    score = example.score(),
                 )
                 .map_err(|err| {
                     miette!("failed to add line to SUMMARY.md for example `{name}`: {err}",)
@@ -80,11 +187,11 @@ pub fn validate() -> miette::Result<Vec<Example>> {
             },
         )?
         .pipe(|(all_previews, summary_md, md_file_with_everything)| {
-            fs::write(ROOT_DIR.join("SUMMARY.md"), summary_md)
+            fs::write(paths.root.join("SUMMARY.md"), summary_md)
                 .map_err(|err| miette!("Failed to write `SUMMARY.md`: {err}"))
                 .map(|()| {
                     fs::write(
-                        ROOT_DIR.join("introduction.md"),
+                        paths.root.join("introduction.md"),
                         format!(
                             "<!-- @generated This file is generated. Do not edit it by hand. -->
 
@@ -128,5 +235,5 @@ You can copy-paste it into Helix and work through it without need for internet!
         })?
         .map_err(|err| miette!("Failed to write `introduction.md`: {err}"))?;
 
-    Ok(examples)
+    Ok(())
 }
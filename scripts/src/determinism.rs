@@ -0,0 +1,103 @@
+//! Find examples which do not produce the same result each time
+//!
+//! An example could depend on timing, such as a prompt which opens slowly or
+//! a completion popup, and only pass sometimes. Such an example is played twice,
+//! each time in its own directory, and the buffer after each step and
+//! the final screen of both runs are compared.
+
+use std::{fs, iter, path::PathBuf};
+
+use miette::{ensure, miette};
+use pretty_assertions::StrComparison;
+
+use crate::{
+    generate_helix_config,
+    parse_example::Example,
+    paths::Paths,
+    recorder::{self, Recorder},
+    snapshot::Accept,
+    terminal::Terminal,
+};
+
+impl Example {
+    /// Directory of a single run of the determinism check
+    pub fn determinism_dir(&self, paths: &Paths, run: usize) -> PathBuf {
+        paths
+            .generated
+            .join("determinism")
+            .join(&self.name)
+            .join(run.to_string())
+    }
+}
+
+/// Play the `example` twice with the `recorder` in the `terminal`, and make sure
+/// that both runs produce the same buffers and final screen.
+///
+/// The snapshots of the previous run are not used, and the generated directory
+/// is only used to hold the directory of each run
+///
+/// # Errors
+///
+/// If a run fails, or the runs differ
+pub fn check(
+    recorder: &dyn Recorder,
+    example: &Example,
+    paths: &Paths,
+    terminal: &Terminal,
+) -> miette::Result<()> {
+    let name = &example.name;
+
+    let play = |run: usize| -> miette::Result<Vec<(String, String)>> {
+        let dir = example.determinism_dir(paths, run);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)
+                .map_err(|err| miette!("Failed to remove `{}`: {err}", dir.display()))?;
+        }
+        fs::create_dir_all(&dir)
+            .map_err(|err| miette!("Failed to create `{}`: {err}", dir.display()))?;
+
+        let paths = Paths {
+            root: paths.root.clone(),
+            generated: dir.clone(),
+            snapshots: dir.join("saved"),
+        };
+        generate_helix_config::generate(&paths.generated)?;
+        recorder::verify(recorder, example, &paths, terminal, Accept::New)?;
+
+        let snapshot_dir = example.generated_snapshot_dir(&paths);
+        let files = iter::once((
+            dir.join(format!("{name}.{}", example.language)),
+            "buffer".to_string(),
+        ))
+        .chain(example.snapshot_steps().into_iter().map(|step| {
+            (
+                snapshot_dir.join(example.snapshot_file(step)),
+                format!("buffer after step {step}"),
+            )
+        }))
+        .chain([(
+            snapshot_dir.join(Example::screen_file()),
+            "final screen".to_string(),
+        )]);
+
+        files
+            .map(|(file, what)| {
+                fs::read_to_string(&file)
+                    .map(|contents| (what, contents))
+                    .map_err(|err| miette!("Failed to read `{}`: {err}", file.display()))
+            })
+            .collect()
+    };
+    let first = play(1)?;
+    let second = play(2)?;
+
+    for ((what, first), (_, second)) in first.iter().zip(&second) {
+        ensure!(
+            first == second,
+            "example `{name}` is not deterministic, the {what} differs between two runs\n\n{}",
+            StrComparison::new(first, second)
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,139 @@
+//! Bind the command of an example to a key in the Helix config
+//!
+//! A command which only runs commands of the keymap is bound to the list of their names,
+//! such as `["select_all", "split_selection_on_newline"]`. Any other command is bound
+//! to a macro, a string of keys in Helix notation which starts with `@`.
+
+use std::ops::Range;
+
+use crate::{
+    helix_mode::{ModeTracker, Role},
+    keymap::Keymap,
+    parse_example::Example,
+    parse_helix_keys::KeyEvent,
+};
+
+/// What the key is bound to
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Binding {
+    /// Keys in Helix notation, such as `@%<A-s>`
+    Macro(String),
+    /// Names of the commands which the keys run
+    Commands(Vec<String>),
+}
+
+/// Text typed into a prompt, such as the regex of `s`
+#[derive(Clone, Debug)]
+pub struct PromptText {
+    /// The text, in the notation of the examples
+    pub text: String,
+    /// Range of [`Example::key_events`]
+    pub key_events: Range<usize>,
+}
+
+/// The command of an example, as it can be bound to a key
+#[derive(Clone, Debug)]
+pub struct ExportedMacro {
+    /// What the key is bound to
+    pub binding: Binding,
+    /// Text of the prompts which the command opens. It likely only works for
+    /// files which are similar to `## Before`
+    pub prompts: Vec<PromptText>,
+}
+
+impl ExportedMacro {
+    /// Snippet of a Helix `config.toml` which binds the `key` of normal mode,
+    /// in Helix notation such as `C-x`, to this macro
+    pub fn snippet(&self, key: &str, title: &str) -> String {
+        let value = match &self.binding {
+            Binding::Macro(keys) => toml::Value::String(keys.clone()),
+            Binding::Commands(commands) => {
+                toml::Value::Array(commands.iter().cloned().map(toml::Value::String).collect())
+            }
+        };
+        let key = toml::Value::String(key.to_string());
+
+        format!("# {title}\n[keys.normal]\n{key} = {value}\n")
+    }
+}
+
+impl Example {
+    /// Turn the command of this example into something that can be bound to a key.
+    ///
+    /// The names of the commands are those of the `keymap`
+    pub fn export_macro(&self, keymap: &Keymap) -> ExportedMacro {
+        let mut tracker = ModeTracker::default();
+        let mut prompts: Vec<PromptText> = Vec::new();
+        let mut is_plain = true;
+
+        for (i, key) in self.key_events.iter().enumerate() {
+            if tracker.is_prompt_open() {
+                // The key which closes the prompt is not a part of its text
+                if key.notation() != "<enter>" && key.notation() != "<esc>" {
+                    match prompts.last_mut() {
+                        Some(prompt) if prompt.key_events.end == i => {
+                            prompt.text.push_str(&key.notation());
+                            prompt.key_events.end = i + 1;
+                        }
+                        _ => prompts.push(PromptText {
+                            text: key.notation(),
+                            key_events: i..i + 1,
+                        }),
+                    }
+                }
+            }
+            if tracker.role(key) == Role::Argument {
+                is_plain = false;
+            }
+            tracker.feed(key);
+        }
+
+        let commands = self
+            .key_sequences()
+            .iter()
+            .map(|sequence| keymap.command(sequence.mode, &sequence.keys))
+            .collect::<Option<Vec<_>>>()
+            .filter(|_| is_plain);
+
+        let binding = commands.map_or_else(
+            || {
+                Binding::Macro(format!(
+                    "@{}",
+                    self.key_events.iter().map(helix_key).collect::<String>()
+                ))
+            },
+            |commands| Binding::Commands(commands.into_iter().map(ToString::to_string).collect()),
+        );
+
+        ExportedMacro { binding, prompts }
+    }
+}
+
+/// The `key` in the notation of Helix, such as `<A-s>` or `<ret>`
+fn helix_key(key: &KeyEvent) -> String {
+    let notation = key.notation();
+    let Some(name) = notation
+        .strip_prefix('<')
+        .and_then(|name| name.strip_suffix('>'))
+    else {
+        return notation;
+    };
+
+    let mut tokens = name.split('-').collect::<Vec<_>>();
+    let code = match tokens.pop() {
+        Some("enter") => "ret",
+        Some(code) => code,
+        None => "",
+    };
+    let modifiers = tokens
+        .into_iter()
+        .map(|modifier| match modifier {
+            "ctrl" => "C-",
+            "alt" => "A-",
+            "shift" => "S-",
+            _ => "Meta-",
+        })
+        .collect::<String>();
+
+    format!("<{modifiers}{code}>")
+}
@@ -0,0 +1,76 @@
+//! Files to reproduce a failed example without recording it again
+//!
+//! The bundle of an example contains the log of Helix, the `.tape` file,
+//! the Helix config and the file as Helix left it
+
+use std::{fs, path::PathBuf};
+
+use miette::miette;
+
+use crate::{parse_example::Example, paths::Paths, terminal::Terminal};
+
+impl Example {
+    /// Directory with the files to reproduce this example once it has failed
+    pub fn failure_dir(&self, paths: &Paths) -> PathBuf {
+        paths.generated.join("failures").join(&self.name)
+    }
+}
+
+/// Collect the files of the failed `example` into its [`Example::failure_dir`].
+///
+/// If the example was not recorded with `vhs`, the `.tape` file is generated
+/// for the `terminal`, so the example can be reproduced with it.
+///
+/// # Errors
+///
+/// If the bundle cannot be written
+pub fn bundle(example: &Example, paths: &Paths, terminal: &Terminal) -> miette::Result<PathBuf> {
+    let name = &example.name;
+    let dir = example.failure_dir(paths);
+
+    // Files of an earlier failure
+    if dir.exists() {
+        fs::remove_dir_all(&dir)
+            .map_err(|err| miette!("Failed to remove `{}`: {err}", dir.display()))?;
+    }
+    fs::create_dir_all(&dir)
+        .map_err(|err| miette!("Failed to create `{}`: {err}", dir.display()))?;
+
+    let tape_file = format!("{name}.tape");
+    let from_generated = |file: &str| (paths.generated.join(file), file.to_string());
+    for (from, file) in [
+        from_generated(&example.log_file()),
+        from_generated(&tape_file),
+        from_generated("helix-config.toml"),
+        from_generated(&format!("{name}.{}", example.language)),
+        (
+            example
+                .generated_snapshot_dir(paths)
+                .join(Example::screen_file()),
+            Example::screen_file().to_string(),
+        ),
+    ] {
+        if from.exists() {
+            fs::copy(&from, dir.join(&file)).map_err(|err| {
+                miette!(
+                    "Failed to copy `{}` for example `{name}`: {err}",
+                    from.display()
+                )
+            })?;
+        }
+    }
+
+    if !dir.join(&tape_file).exists() {
+        let generated = paths
+            .generated
+            .canonicalize()
+            .map_err(|err| miette!("Failed to find the generated directory: {err}"))?;
+        fs::write(
+            dir.join(&tape_file),
+            example.tape(&generated, terminal).to_string(),
+        )
+        .map_err(|err| miette!("Failed to create `{tape_file}` for example `{name}`: {err}"))?;
+    }
+
+    Ok(dir)
+}
@@ -1,9 +1,29 @@
-//! The helix config used by the recordings.
+//! The helix configs used by the recordings, and when practising an example.
 
-use std::fs;
+use std::{fs, path::Path};
 
+use miette::miette;
 use tap::Pipe as _;
 
+/// Opens the command prompt in every mode, including insert mode.
+///
+/// Used by the hidden commands in the `.tape` file which capture the
+/// state of the buffer in the middle of an example
+pub const COMMAND_MODE: &str = r#"Ctrl+"q""#;
+
+/// [`COMMAND_MODE`], as it is sent through a terminal
+pub const COMMAND_MODE_BYTES: &[u8] = b"\x11";
+
+/// Does nothing in every mode. Helix clears the statusline on each key, so it is
+/// pressed after the hidden commands to remove their messages before the demo continues
+pub const CLEAR_STATUS: &str = r#"Ctrl+"l""#;
+
+/// [`CLEAR_STATUS`], as it is sent through a terminal
+pub const CLEAR_STATUS_BYTES: &[u8] = b"\x0c";
+
+/// Key in `vhs` notation which replaces the key `ch` pressed with modifiers `mods`.
+///
+/// `None` if the key does not need to be replaced
 pub fn remap(mods: &str, ch: char) -> Option<String> {
     match (mods, ch) {
         // Remap alt keys because `vhs` cannot handle them
@@ -17,7 +37,12 @@ pub fn remap(mods: &str, ch: char) -> Option<String> {
     .pipe(Some)
 }
 
-pub fn generate() {
+/// Write the config into `dir`, as `helix-config.toml`
+///
+/// # Errors
+///
+/// If the file cannot be written
+pub fn generate(dir: &Path) -> miette::Result<()> {
     let remapped = r#"
 # Original: Alt + s
 C-s = "split_selection_on_newline"
@@ -36,10 +61,13 @@ C-d = "delete_selection_noyank"
 
 #"C-M" = "remove_primary_selection"
 
+# Used to capture snapshots
+C-q = "command_mode"
+C-l = "no_op"
 "#;
 
     fs::write(
-        crate::command::GENERATED_DIR.join("helix-config.toml"),
+        dir.join("helix-config.toml"),
         format!(
             r#"theme = "base16_transparent"
             
@@ -55,8 +83,28 @@ layout = ["line-numbers", "spacer"]
 {remapped}
 
 [keys.select]
-{remapped}"#
+{remapped}
+
+[keys.insert]
+C-q = "command_mode"
+C-l = "no_op""#
         ),
     )
-    .unwrap();
+    .map_err(|err| miette!("Failed to write the Helix config: {err}"))
+}
+
+/// Write a config with the default keys of Helix into `dir`, as `helix-config.toml`,
+/// so that practising an example feels like the Helix of the reader.
+///
+/// Like in the recordings, the file is not formatted when it is saved
+///
+/// # Errors
+///
+/// If the file cannot be written
+pub fn generate_default_keys(dir: &Path) -> miette::Result<()> {
+    fs::write(
+        dir.join("helix-config.toml"),
+        "[editor]\nauto-format = false\nlsp.enable = false\n",
+    )
+    .map_err(|err| miette!("Failed to write the Helix config: {err}"))
 }
@@ -1,40 +1,109 @@
 //! Contains `Display` implementations that turn into the `.tape` file processed by `VHS` into a `.mp4` video
 
-use std::fmt::Display;
+use std::{fmt::Display, path::Path};
 
 use crate::{
-    generate_helix_config,
+    generate_helix_config, helix_log,
     parse_example::Example,
     parse_helix_keys::{KeyCode, KeyEvent, KeyModifiers, MediaKeyCode, ModifierKeyCode},
+    recorder::{self, Format},
+    terminal::Terminal,
 };
 
-impl Display for Example {
+/// The `.tape` file of an example
+pub struct Tape<'a> {
+    /// The example which is recorded
+    example: &'a Example,
+    /// Directory with the generated files, which is where the recording happens
+    dir: &'a Path,
+    /// Terminal in which the example is recorded
+    terminal: &'a Terminal,
+}
+
+impl Example {
+    /// The `.tape` file of this example, which records it inside of `dir` in the `terminal`
+    pub fn tape<'a>(&'a self, dir: &'a Path, terminal: &'a Terminal) -> Tape<'a> {
+        Tape {
+            example: self,
+            dir,
+            terminal,
+        }
+    }
+}
+
+impl Display for Tape<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            example,
+            dir,
+            terminal: Terminal { theme, size, helix },
+        } = self;
+        let dir = dir.display();
+
         writeln!(
             f,
-            r#"Output src/generated/{name}.mp4
-Require hx
+            r#"Output {dir}/{output}
+Output {dir}/snapshots/{name}/{screen_frames}
+Require {helix}
 
 Hide
 Set Shell "bash"
-Set FontSize 20
-Set Width 1200
-Set Height 600
+Set FontSize {font_size}
+Set Width {width}
+Set Height {height}
 Set Padding 0
-Set Theme "Catppuccin Mocha"
-Set TypingSpeed 150ms
-Type "hx -c src/generated/helix-config.toml src/generated/{name}.{ext}"
+Set Theme "{theme}"
+Set TypingSpeed {typing_speed}ms
+Type "{helix} -c {dir}/helix-config.toml {log} {dir}/{name}.{ext}"
 Enter
-Type ":cd src/generated"
+Type ":cd {dir}"
 Enter
 Show
 "#,
-            name = self.name,
-            ext = self.language
+            name = example.name,
+            ext = example.language,
+            typing_speed = recorder::TYPING_SPEED.as_millis(),
+            output = example.demo_file(Format::Mp4, theme.appearance),
+            screen_frames = recorder::SCREEN_FRAMES_FILE,
+            log = helix_log::args(&self.dir.join(example.log_file())).join(" "),
+            theme = theme.name,
+            font_size = size.font_size,
+            helix = helix.program.display(),
+            width = size.width,
+            height = size.height,
         )?;
 
-        for key in &self.key_events {
-            writeln!(f, "{key}")?;
+        let snapshot_steps = example.snapshot_steps();
+
+        for (i, step) in example.steps.iter().enumerate() {
+            for key in &example.key_events[step.key_events.clone()] {
+                writeln!(f, "{key}")?;
+            }
+
+            let step = i + 1;
+            if snapshot_steps.contains(&step) {
+                // Capture the buffer and selections after this step,
+                // without it being visible in the demo
+                writeln!(
+                    f,
+                    r#"Screenshot {dir}/snapshots/{name}/{screenshot}
+Hide
+{command_mode}
+Type@1ms "write!"
+Enter
+{command_mode}
+Type@1ms "run-shell-command cp {name}.{ext} snapshots/{name}/{snapshot}"
+Enter
+{clear_status}
+Show"#,
+                    name = example.name,
+                    ext = example.language,
+                    screenshot = Example::screenshot_file(step, theme.appearance),
+                    snapshot = example.snapshot_file(step),
+                    command_mode = generate_helix_config::COMMAND_MODE,
+                    clear_status = generate_helix_config::CLEAR_STATUS,
+                )?;
+            }
         }
 
         f.write_str(
@@ -0,0 +1,430 @@
+//! Search for shorter keys which still turn `## Before` into `## After`
+//!
+//! The command of an example is rewritten in several ways, and each rewrite with
+//! a better score is typed into Helix to check that it produces `## After`.
+//! Once a rewrite works, the search continues from it. The rewrites are:
+//!
+//! - dropping a command, or two commands in a row, which may be redundant
+//! - replacing a run of the same key, such as `jjjj`, with a count
+//! - merging two motions in a row into one with a count, such as `jj` into `2j`
+//!   or `we` into `2e`, see [`MOTION_FAMILIES`]
+//! - replacing keys with shorter ones which select the same text, see [`ALTERNATIVES`]
+
+use std::{
+    collections::HashSet,
+    fs,
+    ops::Range,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use miette::{ensure, miette};
+use rayon::iter::{
+    IndexedParallelIterator as _, IntoParallelRefIterator as _, ParallelIterator as _,
+};
+
+use crate::{
+    generate_helix_config,
+    helix_mode::{Mode, ModeTracker, Role},
+    oracle,
+    parse_example::Example,
+    parse_helix_keys::{KeyEvent, parse_keys},
+    paths::Paths,
+    score::{self, Score},
+    terminal::Terminal,
+};
+
+/// Keys, and shorter keys which usually do the same
+pub const ALTERNATIVES: &[(&str, &str)] = &[
+    ("gl", "<end>"),
+    ("gh", "<home>"),
+    ("ggvge", "%"),
+    ("di", "c"),
+    (r"s\n<enter>", "<alt-s>"),
+];
+
+/// Motions which move by the same unit in the same direction. Two of them in a row
+/// can be merged into the second one with the sum of their counts
+pub const MOTION_FAMILIES: &[&[&str]] = &[
+    &["h", "<left>"],
+    &["j", "<down>"],
+    &["k", "<up>"],
+    &["l", "<right>"],
+    &["w", "e"],
+    &["W", "E"],
+    &["b"],
+    &["B"],
+];
+
+/// Keys which have been rewritten from the command of an example
+#[derive(Clone, Debug)]
+pub struct Rewrite {
+    /// The new keys
+    pub keys: Vec<KeyEvent>,
+    /// Score of the new keys
+    pub score: Score,
+    /// What has changed, such as "dropped `;`"
+    pub description: String,
+}
+
+/// Outcome of the search
+#[derive(Clone, Debug)]
+pub struct Golfed {
+    /// Score of the command of the example
+    pub score: Score,
+    /// Each rewrite which is better than the previous one, and produces `## After`
+    pub found: Vec<Rewrite>,
+    /// How many rewrites have been typed into Helix
+    pub tried: usize,
+    /// Whether the search stopped because the time budget ran out
+    pub out_of_time: bool,
+}
+
+/// The keys in the notation of the examples
+pub fn notation(keys: &[KeyEvent]) -> String {
+    keys.iter().map(KeyEvent::notation).collect()
+}
+
+/// Key events of each command, with the keys that it waits for such as its count
+/// or the text of its prompt. Each key of insert mode is its own command
+pub fn commands(keys: &[KeyEvent]) -> Vec<Range<usize>> {
+    let mut tracker = ModeTracker::default();
+    let mut commands: Vec<Range<usize>> = Vec::new();
+
+    for (i, key) in keys.iter().enumerate() {
+        match commands.last_mut() {
+            Some(command) if !tracker.is_settled() => command.end = i + 1,
+            _ => commands.push(i..i + 1),
+        }
+        tracker.feed(key);
+    }
+
+    commands
+}
+
+/// Every rewrite of the `keys`, the best score first
+pub fn rewrites(keys: &[KeyEvent]) -> Vec<Rewrite> {
+    let commands = commands(keys);
+    let rewrite = |range: Range<usize>, replacement: &[KeyEvent], description: String| {
+        let keys = [&keys[..range.start], replacement, &keys[range.end..]].concat();
+        Rewrite {
+            score: score::score(&keys),
+            keys,
+            description,
+        }
+    };
+    let mut rewrites = Vec::new();
+
+    // Commands which may be redundant
+    for (i, command) in commands.iter().enumerate() {
+        rewrites.push(rewrite(
+            command.clone(),
+            &[],
+            format!("dropped `{}`", notation(&keys[command.clone()])),
+        ));
+        if let Some(next) = commands.get(i + 1) {
+            let range = command.start..next.end;
+            rewrites.push(rewrite(
+                range.clone(),
+                &[],
+                format!("dropped `{}`", notation(&keys[range])),
+            ));
+        }
+    }
+
+    // Runs of the same key, which can be typed once with a count
+    let mut tracker = ModeTracker::default();
+    let mut modes = Vec::with_capacity(keys.len());
+    for key in keys {
+        modes.push((tracker.mode(), tracker.role(key)));
+        tracker.feed(key);
+    }
+    let mut i = 0;
+    while i < commands.len() {
+        let first = commands[i].clone();
+        let is_repeatable = first.len() == 1
+            && matches!(
+                modes[first.start],
+                (
+                    Mode::Normal | Mode::Select,
+                    Role::Binding {
+                        continues: false,
+                        ..
+                    }
+                )
+            );
+        let run = commands[i..]
+            .iter()
+            .take_while(|command| keys[(*command).clone()] == keys[first.clone()])
+            .count();
+        if is_repeatable && run >= 3 {
+            let range = first.start..commands[i + run - 1].end;
+            let count = format!("{run}{}", keys[first.start].notation());
+            if let Ok(replacement) = parse_keys(&count, "golf") {
+                rewrites.push(rewrite(
+                    range.clone(),
+                    &replacement,
+                    format!("replaced `{}` with `{count}`", notation(&keys[range])),
+                ));
+            }
+        }
+        i += run.max(1);
+    }
+
+    // Motions in a row, which can be typed once with a count
+    let motion = |command: &Range<usize>| {
+        let (last, count) = keys[command.clone()].split_last()?;
+        let count = if count.is_empty() {
+            1
+        } else {
+            notation(count)
+                .parse::<usize>()
+                .ok()
+                .filter(|&count| count > 0)?
+        };
+        let is_binding = matches!(
+            modes[command.end - 1],
+            (
+                Mode::Normal | Mode::Select,
+                Role::Binding {
+                    continues: false,
+                    ..
+                }
+            )
+        );
+        let key = last.notation();
+        let family = MOTION_FAMILIES
+            .iter()
+            .position(|family| family.contains(&key.as_str()))?;
+        is_binding.then_some((count, key, family))
+    };
+    for pair in commands.windows(2) {
+        let (Some((count, _, family)), Some((next_count, next_key, next_family))) =
+            (motion(&pair[0]), motion(&pair[1]))
+        else {
+            continue;
+        };
+        let merged = format!("{}{next_key}", count + next_count);
+        if family == next_family
+            && let Ok(replacement) = parse_keys(&merged, "golf")
+        {
+            let range = pair[0].start..pair[1].end;
+            rewrites.push(rewrite(
+                range.clone(),
+                &replacement,
+                format!("merged `{}` into `{merged}`", notation(&keys[range])),
+            ));
+        }
+    }
+
+    // Keys which have a shorter equivalent
+    let starts = commands
+        .iter()
+        .map(|command| command.start)
+        .chain([keys.len()])
+        .collect::<HashSet<_>>();
+    for (from, to) in ALTERNATIVES {
+        let (Ok(from_keys), Ok(to_keys)) = (parse_keys(from, "golf"), parse_keys(to, "golf"))
+        else {
+            continue;
+        };
+        for start in commands.iter().map(|command| command.start) {
+            let end = start + from_keys.len();
+            if keys.get(start..end) == Some(&from_keys[..]) && starts.contains(&end) {
+                rewrites.push(rewrite(
+                    start..end,
+                    &to_keys,
+                    format!("replaced `{from}` with `{to}`"),
+                ));
+            }
+        }
+    }
+
+    rewrites.sort_by_key(|rewrite| rank(rewrite.score));
+    // Dropping any key of a run gives the same keys
+    let mut seen = HashSet::new();
+    rewrites.retain(|rewrite| seen.insert(notation(&rewrite.keys)));
+    rewrites
+}
+
+/// Order of the scores, lower is better
+const fn rank(score: Score) -> (usize, usize, usize) {
+    (score.keystrokes, score.commands, score.inserted_characters)
+}
+
+/// Search for keys which are shorter than the command of the `example`, and produce `## After`
+/// when typed into Helix of the `terminal`. Stops once `budget` has passed.
+///
+/// Each rewrite is typed in its own directory inside of the generated directory,
+/// and as many are typed at the same time as there are threads
+///
+/// # Errors
+///
+/// If Helix cannot be started, or the command of the example does not produce `## After`
+pub fn golf(
+    example: &Example,
+    paths: &Paths,
+    terminal: &Terminal,
+    budget: Duration,
+) -> miette::Result<Golfed> {
+    let start = Instant::now();
+    let name = &example.name;
+
+    let dirs = (0..rayon::current_num_threads().max(1))
+        .map(|i| {
+            let dir = paths.generated.join("golf").join(name).join(i.to_string());
+            fs::create_dir_all(&dir)
+                .map_err(|err| miette!("Failed to create `{}`: {err}", dir.display()))?;
+            generate_helix_config::generate(&dir)?;
+            dir.canonicalize()
+                .map_err(|err| miette!("Failed to find `{}`: {err}", dir.display()))
+        })
+        .collect::<miette::Result<Vec<PathBuf>>>()?;
+
+    // Otherwise, no rewrite would work either
+    ensure!(
+        oracle::solves(example, &example.key_events, &dirs[0], terminal)?,
+        "the command of example `{name}` does not produce `## After`"
+    );
+
+    let score = example.score();
+    let mut golfed = Golfed {
+        score,
+        found: Vec::new(),
+        tried: 0,
+        out_of_time: false,
+    };
+    let mut best = (example.key_events.clone(), score);
+    let mut seen = HashSet::from([notation(&example.key_events)]);
+
+    'search: loop {
+        let rewrites = rewrites(&best.0)
+            .into_iter()
+            .filter(|rewrite| rank(rewrite.score) < rank(best.1))
+            .filter(|rewrite| seen.insert(notation(&rewrite.keys)))
+            .collect::<Vec<_>>();
+
+        let mut improved = None;
+        for batch in rewrites.chunks(dirs.len()) {
+            // Each thread checks the budget before starting Helix, `None` if it has run out
+            let solves = batch
+                .par_iter()
+                .zip(&dirs)
+                .map(|(rewrite, dir)| {
+                    (start.elapsed() < budget).then(|| {
+                        oracle::solves(example, &rewrite.keys, dir, terminal).unwrap_or(false)
+                    })
+                })
+                .collect::<Vec<_>>();
+            golfed.tried += solves.iter().flatten().count();
+
+            // The rewrites are sorted, so the first one which works is the best
+            if let Some(i) = solves.iter().position(|solves| *solves == Some(true)) {
+                improved = Some(batch[i].clone());
+                break;
+            }
+            if solves.contains(&None) {
+                golfed.out_of_time = true;
+                break 'search;
+            }
+        }
+
+        let Some(rewrite) = improved else {
+            break;
+        };
+        best = (rewrite.keys.clone(), rewrite.score);
+        golfed.found.push(rewrite);
+    }
+
+    Ok(golfed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Keys in the notation of the examples
+    fn keys(keys: &str) -> Vec<KeyEvent> {
+        parse_keys(keys, "test").unwrap()
+    }
+
+    /// Keys of each command of the `keys`
+    fn commands_of(keys_str: &str) -> Vec<String> {
+        let keys = keys(keys_str);
+        commands(&keys)
+            .into_iter()
+            .map(|command| notation(&keys[command]))
+            .collect()
+    }
+
+    /// Descriptions of the rewrites of the `keys`
+    fn rewrites_of(keys_str: &str) -> Vec<String> {
+        rewrites(&keys(keys_str))
+            .into_iter()
+            .map(|rewrite| rewrite.description)
+            .collect()
+    }
+
+    #[test]
+    fn commands_include_their_count() {
+        assert_eq!(commands_of("3jd"), ["3j", "d"]);
+        assert_eq!(commands_of("12wd"), ["12w", "d"]);
+    }
+
+    #[test]
+    fn commands_include_the_keys_they_wait_for() {
+        assert_eq!(commands_of("ggfxd"), ["gg", "fx", "d"]);
+        assert_eq!(commands_of(r#""ayp"#), [r#""ay"#, "p"]);
+        assert_eq!(commands_of("ms(mi("), ["ms(", "mi("]);
+        assert_eq!(commands_of(":w<enter>x"), [":w<enter>", "x"]);
+    }
+
+    #[test]
+    fn each_key_of_insert_mode_is_a_command() {
+        assert_eq!(commands_of("ihi<esc>"), ["i", "h", "i", "<esc>"]);
+    }
+
+    #[test]
+    fn rewrites_runs_with_a_count() {
+        assert!(rewrites_of("jjjjd").contains(&"replaced `jjjj` with `4j`".to_string()));
+    }
+
+    #[test]
+    fn merges_motions_in_a_row() {
+        assert!(rewrites_of("jjd").contains(&"merged `jj` into `2j`".to_string()));
+        assert!(rewrites_of("wed").contains(&"merged `we` into `2e`".to_string()));
+        assert!(rewrites_of("2jjd").contains(&"merged `2jj` into `3j`".to_string()));
+        // Motions in different directions
+        assert!(
+            !rewrites_of("wbd")
+                .iter()
+                .any(|rewrite| rewrite.starts_with("merged"))
+        );
+        // Keys of insert mode are text, not motions
+        assert!(
+            !rewrites_of("ijj<esc>")
+                .iter()
+                .any(|rewrite| rewrite.starts_with("merged"))
+        );
+    }
+
+    #[test]
+    fn rewrites_are_sorted_and_unique() {
+        let rewrites = rewrites(&keys("ggvgejjjd"));
+        assert!(
+            rewrites
+                .windows(2)
+                .all(|pair| rank(pair[0].score) <= rank(pair[1].score))
+        );
+        let unique = rewrites
+            .iter()
+            .map(|rewrite| notation(&rewrite.keys))
+            .collect::<HashSet<_>>();
+        assert_eq!(unique.len(), rewrites.len());
+        assert!(
+            rewrites
+                .iter()
+                .any(|rewrite| notation(&rewrite.keys) == "%jjjd")
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Errors and warnings that Helix shows in the statusline while an example is played
+//!
+//! Helix writes each message of the statusline into its log, for example
+//! `editor error: No selections remaining`. Even if the keys after it still
+//! produce `## After`, such an example is confusing to watch.
+
+use std::{fs, path::Path};
+
+use miette::miette;
+
+use crate::parse_example::Example;
+
+/// Arguments which make Helix log the messages of the statusline into `log_file`
+pub fn args(log_file: &Path) -> [String; 3] {
+    [
+        "--log".to_string(),
+        log_file.display().to_string(),
+        "-vv".to_string(),
+    ]
+}
+
+/// How severe a message of the statusline is
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Level {
+    /// Shown in red
+    Error,
+    /// Shown in yellow
+    Warning,
+}
+
+/// A message which Helix has shown in the statusline
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Message {
+    /// How severe it is
+    pub level: Level,
+    /// Text of the message
+    pub text: String,
+}
+
+impl Example {
+    /// Name of the file where Helix logs while it plays this example,
+    /// relative to the generated directory
+    pub fn log_file(&self) -> String {
+        format!("{}.log", self.name)
+    }
+}
+
+/// Errors and warnings in the `log` of Helix
+pub fn messages(log: &str) -> Vec<Message> {
+    log.lines()
+        .filter_map(|line| {
+            [
+                ("editor error: ", Level::Error),
+                ("editor warning: ", Level::Warning),
+            ]
+            .into_iter()
+            .find_map(|(prefix, level)| {
+                line.split_once(prefix).map(|(_, text)| Message {
+                    level,
+                    text: text.to_string(),
+                })
+            })
+        })
+        .collect()
+}
+
+/// Contents of the log in `dir` of the `example`, starting at byte `offset`
+pub fn read(example: &Example, dir: &Path, offset: usize) -> String {
+    fs::read_to_string(dir.join(example.log_file()))
+        .ok()
+        .and_then(|log| log.get(offset..).map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// The example failed, because Helix showed the `message` after the key at `index`.
+///
+/// If the key is not known, only the message is reported
+pub fn error(example: &Example, message: &Message, index: Option<usize>) -> miette::Report {
+    let level = match message.level {
+        Level::Error => "an error",
+        Level::Warning => "a warning",
+    };
+    let after = index
+        .and_then(|index| {
+            example.key_events.get(index).map(|key| {
+                format!(
+                    " after key {} of {} `{}`",
+                    index + 1,
+                    example.key_events.len(),
+                    key.notation()
+                )
+            })
+        })
+        .unwrap_or_default();
+
+    miette!(
+        "Helix showed {level}{after} in example `{}`: {}",
+        example.name,
+        message.text
+    )
+}
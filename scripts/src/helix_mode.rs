@@ -0,0 +1,201 @@
+//! Follow which mode Helix is in as it receives each key of an example
+//!
+//! This is an approximation of Helix's default keymap. It only knows about
+//! the keys which change the mode, open a prompt or wait for another key.
+
+use crate::parse_helix_keys::{KeyCode, KeyEvent, KeyModifiers};
+
+/// The editing mode of Helix
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Mode {
+    /// Default mode
+    #[default]
+    Normal,
+    /// Entered with `v`. Motions extend the selection
+    Select,
+    /// Typed characters are inserted into the document
+    Insert,
+}
+
+/// Keys which Helix has received, but has not yet acted on
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Pending {
+    /// Helix will act on the next key that it receives
+    #[default]
+    Nothing,
+    /// A count such as `5` in `5)` has been typed
+    Count,
+    /// The next `n` keys are characters which complete the command,
+    /// for example `x` in `fx`
+    Chars(u8),
+    /// `"` has been typed, waiting for the name of the register
+    RegisterName,
+    /// A register has been selected with `"`, waiting for the command
+    /// which will use the register
+    Register,
+    /// Inside of a sub-menu such as `g` or `<space>`
+    Menu,
+    /// Inside of the `m` (match) sub-menu
+    MatchMenu,
+    /// Inside of the sticky `Z` (view) sub-menu, which stays open until `<esc>`
+    StickyMenu,
+    /// A prompt such as `s` or `:` is open, until `<enter>` or `<esc>`
+    Prompt,
+}
+
+/// How Helix interprets the next key
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// The key is looked up in the keymap of the `mode`
+    Binding {
+        /// Mode whose keymap contains the key
+        mode: Mode,
+        /// The key follows the previous key in a sub-menu, such as `g` in `gg`
+        continues: bool,
+    },
+    /// The key is not looked up in the keymap, such as `x` in `fx`,
+    /// a count or the text of a prompt
+    Argument,
+}
+
+/// Follows the mode and the pending keys of Helix
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ModeTracker {
+    /// Current mode
+    mode: Mode,
+    /// Keys waiting for more input
+    pending: Pending,
+}
+
+impl ModeTracker {
+    /// Whether Helix has acted on all of the keys it received, and no prompt is open
+    pub fn is_settled(self) -> bool {
+        self.pending == Pending::Nothing
+    }
+
+    /// Whether a prompt is open, which receives the next keys as text
+    pub fn is_prompt_open(self) -> bool {
+        self.pending == Pending::Prompt
+    }
+
+    /// Current mode
+    pub fn mode(self) -> Mode {
+        self.mode
+    }
+
+    /// How Helix interprets the `key`, if it is received next
+    pub fn role(self, key: &KeyEvent) -> Role {
+        match self.pending {
+            Pending::Chars(_) | Pending::RegisterName | Pending::Prompt => Role::Argument,
+            Pending::Menu | Pending::MatchMenu | Pending::StickyMenu => Role::Binding {
+                mode: self.mode,
+                continues: true,
+            },
+            Pending::Nothing | Pending::Count | Pending::Register => {
+                let is_count = self.mode != Mode::Insert
+                    && key.modifiers.is_empty()
+                    && (matches!(key.code, KeyCode::Char('1'..='9'))
+                        || (self.pending == Pending::Count && key.code == KeyCode::Char('0')));
+                if is_count {
+                    Role::Argument
+                } else {
+                    Role::Binding {
+                        mode: self.mode,
+                        continues: false,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Helix receives the `key`
+    pub fn feed(&mut self, key: &KeyEvent) {
+        let plain = |ch: char| key.code == KeyCode::Char(ch) && key.modifiers.is_empty();
+        let alt = |ch: char| key.code == KeyCode::Char(ch) && key.modifiers == KeyModifiers::ALT;
+        let ctrl =
+            |ch: char| key.code == KeyCode::Char(ch) && key.modifiers == KeyModifiers::CONTROL;
+
+        match self.pending {
+            Pending::Chars(1) | Pending::Menu => {
+                self.pending = Pending::Nothing;
+                return;
+            }
+            Pending::Chars(n) => {
+                self.pending = Pending::Chars(n - 1);
+                return;
+            }
+            Pending::RegisterName => {
+                self.pending = Pending::Register;
+                return;
+            }
+            Pending::MatchMenu => {
+                self.pending = if plain('r') {
+                    Pending::Chars(2)
+                } else if plain('s') || plain('d') || plain('i') || plain('a') {
+                    Pending::Chars(1)
+                } else {
+                    Pending::Nothing
+                };
+                return;
+            }
+            Pending::Prompt => {
+                if key.code == KeyCode::Enter || key.code == KeyCode::Esc {
+                    self.pending = Pending::Nothing;
+                }
+                return;
+            }
+            Pending::StickyMenu => {
+                if key.code == KeyCode::Esc {
+                    self.pending = Pending::Nothing;
+                }
+                return;
+            }
+            Pending::Nothing | Pending::Count | Pending::Register => {}
+        }
+
+        if self.mode == Mode::Insert {
+            if key.code == KeyCode::Esc {
+                self.mode = Mode::Normal;
+            } else if ctrl('r') {
+                self.pending = Pending::Chars(1);
+            }
+            return;
+        }
+
+        let is_digit = matches!(key.code, KeyCode::Char('1'..='9'))
+            || (self.pending == Pending::Count && plain('0'));
+
+        self.pending = if key.modifiers.is_empty() && is_digit {
+            Pending::Count
+        } else if plain('"') {
+            Pending::RegisterName
+        } else if ['f', 't', 'F', 'T', 'r'].into_iter().any(plain) {
+            Pending::Chars(1)
+        } else if ['g', 'z', '[', ']', ' '].into_iter().any(plain) || ctrl('w') {
+            Pending::Menu
+        } else if plain('Z') {
+            Pending::StickyMenu
+        } else if plain('m') {
+            Pending::MatchMenu
+        } else if ['s', 'S', ':', '/', '?', '|', '!', '$', 'K']
+            .into_iter()
+            .any(plain)
+            || ['|', '!', 'K'].into_iter().any(alt)
+        {
+            Pending::Prompt
+        } else {
+            if ['i', 'a', 'I', 'A', 'o', 'O', 'c'].into_iter().any(plain) || alt('c') {
+                self.mode = Mode::Insert;
+            } else if plain('v') {
+                self.mode = if self.mode == Mode::Select {
+                    Mode::Normal
+                } else {
+                    Mode::Select
+                };
+            } else if key.code == KeyCode::Esc {
+                self.mode = Mode::Normal;
+            }
+            Pending::Nothing
+        };
+    }
+}
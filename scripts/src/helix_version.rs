@@ -0,0 +1,137 @@
+//! Versions of Helix, and the `hx` programs which the examples are played with
+//!
+//! Helix changes its keymap between releases, so an example can be tested with
+//! several versions. An example which needs a newer Helix declares the oldest version
+//! that it works with, in a comment before `## Before`:
+//!
+//! ```md
+//! <!-- minimum-helix-version: 24.07 -->
+//! ```
+
+use std::{
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+
+use miette::{bail, miette};
+use serde::{Deserialize, Serialize};
+
+use crate::parse_example::Example;
+
+/// A release of Helix, such as `25.01.1`
+#[derive(
+    Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default,
+)]
+#[serde(try_from = "String", into = "String")]
+pub struct Version {
+    /// Last two digits of the year of the release
+    pub year: u16,
+    /// Month of the release
+    pub month: u16,
+    /// Patch release, which is 0 for the first release of a month
+    pub patch: u16,
+}
+
+impl FromStr for Version {
+    type Err = miette::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let parts = s
+            .split('.')
+            .map(|part| {
+                part.parse::<u16>()
+                    .map_err(|err| miette!("invalid Helix version `{s}`: {err}"))
+            })
+            .collect::<miette::Result<Vec<_>>>()?;
+
+        match parts[..] {
+            [year, month] => Ok(Self {
+                year,
+                month,
+                patch: 0,
+            }),
+            [year, month, patch] => Ok(Self { year, month, patch }),
+            _ => bail!("invalid Helix version `{s}`, expected e.g. `25.01` or `25.01.1`"),
+        }
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}.{:02}", self.year, self.month)?;
+        if self.patch != 0 {
+            write!(f, ".{}", self.patch)?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for Version {
+    type Error = miette::Report;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Version> for String {
+    fn from(version: Version) -> Self {
+        version.to_string()
+    }
+}
+
+impl Example {
+    /// Whether this example works with Helix of the `version`
+    pub fn supports(&self, version: Version) -> bool {
+        self.minimum_helix_version
+            .is_none_or(|minimum| version >= minimum)
+    }
+}
+
+/// A program which runs Helix
+#[derive(Clone, Debug)]
+pub struct Helix {
+    /// Path to the program, or its name if it is in the `PATH`
+    pub program: PathBuf,
+    /// Version that the program reports
+    pub version: Version,
+}
+
+impl Helix {
+    /// Find out the version of the `program`
+    ///
+    /// # Errors
+    ///
+    /// If the program cannot be found, or does not report a version of Helix
+    pub fn new(program: &Path) -> miette::Result<Self> {
+        let program_name = program.display();
+
+        if which::which(program).is_err() {
+            bail!(
+                "ERROR (command `{program_name}` not found): You need to \
+                install Helix in order to generate the demos"
+            );
+        }
+
+        let output = Command::new(program)
+            .arg("--version")
+            .output()
+            .map_err(|err| miette!("Failed to run `{program_name} --version`: {err}"))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        // For example, `helix 25.01.1 (7275b7f8)`
+        let version = stdout
+            .split_whitespace()
+            .nth(1)
+            .ok_or_else(|| miette!("`{program_name} --version` did not print a version"))?
+            .parse()?;
+
+        Ok(Self {
+            program: program.to_path_buf(),
+            version,
+        })
+    }
+}
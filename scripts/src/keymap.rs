@@ -0,0 +1,333 @@
+//! Default keymap of each release of Helix, to find the examples which
+//! a new release could break
+//!
+//! The keymaps are in the `keymaps` directory, and are embedded into the binary.
+//! To add a release, copy the file of the latest release and change the keys
+//! which the release has changed.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display},
+    iter,
+    ops::Range,
+};
+
+use miette::miette;
+
+use crate::{
+    helix_mode::{Mode, ModeTracker, Role},
+    helix_version::Version,
+    parse_example::Example,
+    parse_helix_keys::{KeyEvent, parse_keys},
+};
+
+/// Contents of the keymap of each release
+const KEYMAPS: &[(&str, &str)] = &[
+    ("24.07", include_str!("../keymaps/24.07.txt")),
+    ("25.01", include_str!("../keymaps/25.01.txt")),
+];
+
+/// Versions of Helix whose keymap is known
+pub fn versions() -> Vec<Version> {
+    KEYMAPS
+        .iter()
+        .filter_map(|(version, _)| version.parse().ok())
+        .collect()
+}
+
+/// The default keymap of a release of Helix
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    /// Release which has this keymap
+    pub version: Version,
+    /// Command of each mode and key sequence, in the notation of the examples
+    pub bindings: BTreeMap<(Mode, String), String>,
+}
+
+impl Keymap {
+    /// The keymap of the release `version`, ignoring its patch release
+    ///
+    /// # Errors
+    ///
+    /// If the keymap of the release is not known, or cannot be parsed
+    pub fn of(version: Version) -> miette::Result<Self> {
+        let (name, contents) = KEYMAPS
+            .iter()
+            .find(|(name, _)| {
+                name.parse::<Version>()
+                    .is_ok_and(|known| known.year == version.year && known.month == version.month)
+            })
+            .ok_or_else(|| {
+                miette!(
+                    "the keymap of Helix {version} is not known, only of Helix {}",
+                    versions()
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })?;
+
+        let bindings = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let [mode, keys, command] = line
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .try_into()
+                    .map_err(|_| miette!("invalid line in the keymap of Helix {name}: `{line}`"))?;
+                let mode = match mode {
+                    "normal" => Mode::Normal,
+                    "select" => Mode::Select,
+                    "insert" => Mode::Insert,
+                    _ => {
+                        return Err(miette!(
+                            "invalid mode in the keymap of Helix {name}: `{mode}`"
+                        ));
+                    }
+                };
+                // The same keys can be written in several ways, such as `<A-s>` and `<alt-s>`
+                let keys = parse_keys(keys, &format!("keymaps/{name}.txt"))?
+                    .iter()
+                    .map(KeyEvent::notation)
+                    .collect::<String>();
+
+                Ok(((mode, keys), command.to_string()))
+            })
+            .collect::<miette::Result<_>>()?;
+
+        Ok(Self {
+            version: name.parse()?,
+            bindings,
+        })
+    }
+
+    /// Command which the `keys` run in the `mode`.
+    ///
+    /// Select mode uses the keys of normal mode that it does not have itself
+    pub fn command(&self, mode: Mode, keys: &str) -> Option<&str> {
+        self.bindings
+            .get(&(mode, keys.to_string()))
+            .or_else(|| {
+                (mode == Mode::Select)
+                    .then(|| self.bindings.get(&(Mode::Normal, keys.to_string())))
+                    .flatten()
+            })
+            .map(String::as_str)
+    }
+}
+
+/// Keys which run a different command in a newer release
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Change {
+    /// Mode of the keys
+    pub mode: Mode,
+    /// The keys, in the notation of the examples
+    pub keys: String,
+    /// Command of the older release, `None` if the keys did nothing
+    pub old: Option<String>,
+    /// Command of the newer release, `None` if the keys do nothing
+    pub new: Option<String>,
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let command =
+            |command: &Option<String>| command.as_deref().unwrap_or("nothing").to_string();
+        write!(
+            f,
+            "{:?} mode `{}`: {} -> {}",
+            self.mode,
+            self.keys,
+            command(&self.old),
+            command(&self.new)
+        )
+    }
+}
+
+/// Keys which run a different command in the `new` keymap than in the `old` one
+pub fn diff(old: &Keymap, new: &Keymap) -> Vec<Change> {
+    let keys = old
+        .bindings
+        .keys()
+        .chain(new.bindings.keys())
+        .flat_map(|(mode, keys)| {
+            // Keys of normal mode are also used in select mode
+            let select = (*mode == Mode::Normal).then(|| (Mode::Select, keys.clone()));
+            iter::once((*mode, keys.clone())).chain(select)
+        })
+        .collect::<BTreeSet<_>>();
+
+    keys.into_iter()
+        .filter_map(|(mode, keys)| {
+            let (old_command, new_command) = (old.command(mode, &keys), new.command(mode, &keys));
+            // Select mode only has its own change if it does not just use the keys of normal mode
+            let inherited = mode == Mode::Select
+                && old_command == old.command(Mode::Normal, &keys)
+                && new_command == new.command(Mode::Normal, &keys);
+            (old_command != new_command && !inherited).then(|| Change {
+                mode,
+                old: old_command.map(ToString::to_string),
+                new: new_command.map(ToString::to_string),
+                keys,
+            })
+        })
+        .collect()
+}
+
+/// The change of the `keys` in the `mode`, out of the `changes` made by [`diff()`]
+pub fn find_change<'a>(changes: &'a [Change], mode: Mode, keys: &str) -> Option<&'a Change> {
+    let find = |mode: Mode| {
+        changes
+            .iter()
+            .find(|change| change.mode == mode && change.keys == keys)
+    };
+    find(mode).or_else(|| {
+        // Select mode uses the keys of normal mode, unless it has changed on its own
+        (mode == Mode::Select).then(|| find(Mode::Normal)).flatten()
+    })
+}
+
+/// Keys of an example which are looked up in the keymap together
+#[derive(Clone, Debug)]
+pub struct KeySequence {
+    /// Mode in which the keys are typed
+    pub mode: Mode,
+    /// The keys, in the notation of the examples
+    pub keys: String,
+    /// Range of [`Example::key_events`]
+    pub key_events: Range<usize>,
+}
+
+impl Example {
+    /// Keys of the command which run a command of the keymap.
+    ///
+    /// Does not include the keys which are not looked up in the keymap,
+    /// such as the text of a prompt
+    pub fn key_sequences(&self) -> Vec<KeySequence> {
+        let mut tracker = ModeTracker::default();
+        let mut sequences: Vec<KeySequence> = Vec::new();
+
+        for (i, key) in self.key_events.iter().enumerate() {
+            if let Role::Binding { mode, continues } = tracker.role(key) {
+                match sequences.last_mut() {
+                    Some(sequence) if continues && sequence.key_events.end == i => {
+                        sequence.keys.push_str(&key.notation());
+                        sequence.key_events.end = i + 1;
+                    }
+                    _ => sequences.push(KeySequence {
+                        mode,
+                        keys: key.notation(),
+                        key_events: i..i + 1,
+                    }),
+                }
+            }
+            tracker.feed(key);
+        }
+
+        sequences
+    }
+
+    /// Number of the step (starting at 1) which explains the key at `index`
+    pub fn step_of(&self, index: usize) -> Option<usize> {
+        self.steps
+            .iter()
+            .position(|step| step.key_events.contains(&index))
+            .map(|i| i + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Keymaps of the releases before and after the debugger moved to `<space>G`
+    fn keymaps() -> (Keymap, Keymap) {
+        (
+            Keymap::of("24.07".parse().unwrap()).unwrap(),
+            Keymap::of("25.01".parse().unwrap()).unwrap(),
+        )
+    }
+
+    fn change(keys: &str, old: Option<&str>, new: Option<&str>) -> Change {
+        Change {
+            mode: Mode::Normal,
+            keys: keys.to_string(),
+            old: old.map(ToString::to_string),
+            new: new.map(ToString::to_string),
+        }
+    }
+
+    #[test]
+    fn every_embedded_keymap_parses() {
+        for version in versions() {
+            assert_eq!(Keymap::of(version).unwrap().version, version);
+        }
+        assert!(Keymap::of("23.10".parse().unwrap()).is_err());
+    }
+
+    #[test]
+    fn patch_releases_use_the_keymap_of_their_release() {
+        let keymap = Keymap::of("24.07.1".parse().unwrap()).unwrap();
+        assert_eq!(keymap.version, "24.07".parse().unwrap());
+    }
+
+    #[test]
+    fn select_mode_uses_the_keys_of_normal_mode() {
+        let (keymap, _) = keymaps();
+        assert_eq!(
+            keymap.command(Mode::Select, "j"),
+            Some("extend_visual_line_down")
+        );
+        assert_eq!(keymap.command(Mode::Select, "d"), Some("delete_selection"));
+        assert_eq!(keymap.command(Mode::Insert, "d"), None);
+    }
+
+    #[test]
+    fn diff_of_the_debugger_menu() {
+        let (old, new) = keymaps();
+        let changes = diff(&old, &new);
+
+        assert_eq!(changes.len(), 25);
+        assert!(changes.iter().all(|change| change.mode == Mode::Normal));
+        assert!(changes.contains(&change("<space>g", None, Some("changed_file_picker"))));
+        assert!(changes.contains(&change("<space>gl", Some("dap_launch"), None)));
+        assert!(changes.contains(&change("<space>Gl", None, Some("dap_launch"))));
+        assert!(changes.contains(&change("<space>GE", None, Some("dap_disable_exceptions"))));
+
+        // Select mode has no changes of its own, but finds those of normal mode
+        assert_eq!(
+            find_change(&changes, Mode::Select, "<space>gl"),
+            Some(&change("<space>gl", Some("dap_launch"), None))
+        );
+        assert_eq!(find_change(&changes, Mode::Normal, "w"), None);
+    }
+
+    #[test]
+    fn diff_of_the_same_keymap_is_empty() {
+        let (old, _) = keymaps();
+        assert_eq!(diff(&old, &old), []);
+    }
+
+    #[test]
+    fn diff_of_a_key_changed_in_select_mode_only() {
+        let (old, _) = keymaps();
+        let mut new = old.clone();
+        new.bindings.insert(
+            (Mode::Select, "w".to_string()),
+            "move_next_word_end".to_string(),
+        );
+
+        assert_eq!(
+            diff(&old, &new),
+            [Change {
+                mode: Mode::Select,
+                keys: "w".to_string(),
+                old: old.command(Mode::Select, "w").map(ToString::to_string),
+                new: Some("move_next_word_end".to_string()),
+            }]
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! Parse, record and test [Helix Golf](https://nik-rev.github.io/helix-golf) examples.
+//!
+//! Each example is a markdown file with a title, the text `## Before` and `## After`
+//! it was edited, the `## Command` typed into Helix and a list explaining each step.
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use helix_golf::{
+//!     Example, Paths, generate_helix_config,
+//!     helix_version::Helix,
+//!     recorder,
+//!     snapshot::Accept,
+//!     terminal::{Bounds, Size, Terminal},
+//!     theme,
+//! };
+//!
+//! let paths = Paths::new(Path::new("src"), None);
+//! let example = Example::parse(&paths.root.join("text_into_array.md"))?;
+//!
+//! // Keys of the command, in Helix notation
+//! for key in &example.key_events {
+//!     println!("{key:?}");
+//! }
+//!
+//! // Type the command into Helix, and check that it produces `## After`
+//! generate_helix_config::generate(&paths.generated)?;
+//! let (size, _warning) = Size::fit(&example, &Bounds::default());
+//! let terminal = Terminal {
+//!     theme: theme::CATPPUCCIN_MOCHA,
+//!     size,
+//!     helix: Helix::new(Path::new("hx"))?,
+//! };
+//! recorder::verify(
+//!     &recorder::Vhs::default(),
+//!     &example,
+//!     &paths,
+//!     &terminal,
+//!     Accept::New,
+//! )?;
+//! # Ok::<(), miette::Report>(())
+//! ```
+
+pub mod browse;
+pub mod captions;
+pub mod check;
+pub mod determinism;
+pub mod export_macro;
+pub mod failure;
+pub mod generate_helix_config;
+pub mod generate_tape_file;
+pub mod golf;
+pub mod helix_log;
+pub mod helix_mode;
+pub mod helix_version;
+pub mod keymap;
+pub mod lint;
+pub mod manifest;
+pub mod oracle;
+pub mod parse_example;
+pub mod parse_helix_keys;
+pub mod paths;
+pub mod practice;
+pub mod pty;
+pub mod recorder;
+pub mod report;
+pub mod score;
+pub mod snapshot;
+pub mod svg;
+pub mod terminal;
+pub mod theme;
+pub mod timeline;
+pub mod translate;
+
+pub use generate_tape_file::Tape;
+pub use manifest::Manifest;
+pub use parse_example::{Example, Step};
+pub use parse_helix_keys::{KeyCode, KeyEvent, KeyModifiers, ParseKeysError, parse_keys};
+pub use paths::Paths;
+pub use recorder::{Asciicast, Format, Recorder, Svg, VerifyOnly, Vhs};
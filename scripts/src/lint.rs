@@ -0,0 +1,417 @@
+//! Find keys of a command which have a known shorter equivalent, without typing them into Helix
+//!
+//! Unlike [`crate::golf`], the lints only look at the keys, so they are quick enough to
+//! run on every example. The keys which they suggest usually do the same, but not always.
+
+use std::ops::Range;
+
+use miette::{Diagnostic, NamedSource, SourceSpan};
+
+use crate::{
+    golf::{self, notation},
+    helix_mode::{Mode, ModeTracker, Role},
+    parse_example::Example,
+    parse_helix_keys::parse_keys,
+    translate,
+};
+
+/// Motions which can be given a count instead of being pressed several times
+const MOTIONS: &[&str] = &[
+    "h", "j", "k", "l", "w", "b", "e", "W", "B", "E", "<left>", "<right>", "<up>", "<down>",
+];
+
+/// Motions which leave a single selection of one character in normal mode,
+/// so `;` after them has nothing to collapse
+const COLLAPSING: &[&str] = &[
+    "h", "j", "k", "l", "<left>", "<right>", "<up>", "<down>", "<home>", "<end>", "gh", "gl", "gs",
+    "gg", "ge", ";",
+];
+
+/// Keys which enter insert mode, and keys of insert mode which usually do the same
+/// right after leaving it with `<esc>`
+const REENTER_INSERT: &[(&str, &str)] = &[
+    ("i", ""),
+    ("a", "<right>"),
+    ("I", "<home>"),
+    ("A", "<end>"),
+    ("o", "<end><enter>"),
+    ("O", "<home><enter><up>"),
+    ("c", "<del>"),
+    ("<alt-c>", "<del>"),
+];
+
+/// Keys of a command which can be replaced with shorter ones
+#[derive(Clone, Debug)]
+pub struct Lint {
+    /// What can be improved, such as "`jjjj` repeats the same motion"
+    pub message: String,
+    /// Range of [`Example::key_events`]
+    pub key_events: Range<usize>,
+    /// Keys which can replace the key events, empty if they can be dropped
+    pub replacement: String,
+}
+
+/// A [`Lint`], pointing at the keys inside of the markdown file
+#[derive(thiserror::Error, Debug, Diagnostic)]
+#[error("{message}")]
+#[diagnostic(severity(Warning), help("{help}"))]
+pub struct LintWarning {
+    /// Contents of the markdown file
+    #[source_code]
+    src: NamedSource<String>,
+    /// What can be improved
+    message: String,
+    /// The suggested replacement
+    help: String,
+    /// The keys inside of the command block
+    #[label("these keys")]
+    span: SourceSpan,
+}
+
+impl Example {
+    /// Keys of the command which have a known shorter equivalent
+    pub fn lints(&self) -> Vec<Lint> {
+        let keys = &self.key_events;
+        let commands = golf::commands(keys);
+        let mut tracker = ModeTracker::default();
+        let mut roles = Vec::with_capacity(keys.len());
+        // Mode once each key has been pressed
+        let mut modes = Vec::with_capacity(keys.len());
+        for key in keys {
+            roles.push(tracker.role(key));
+            tracker.feed(key);
+            modes.push(tracker.mode());
+        }
+        // Keys of the command without its count, if it is a binding of normal or select mode
+        let binding = |command: &Range<usize>| {
+            let Role::Binding {
+                mode: mode @ (Mode::Normal | Mode::Select),
+                ..
+            } = roles[command.start]
+            else {
+                return None;
+            };
+            let keys = notation(&keys[command.clone()]);
+            Some((
+                mode,
+                keys.trim_start_matches(|ch: char| ch.is_ascii_digit())
+                    .to_string(),
+            ))
+        };
+        let mut lints = Vec::new();
+
+        // Runs of the same motion, which can be typed once with a count
+        let mut i = 0;
+        while i < commands.len() {
+            let first = commands[i].clone();
+            let run = commands[i..]
+                .iter()
+                .take_while(|command| keys[(*command).clone()] == keys[first.clone()])
+                .count();
+            let key = notation(&keys[first.clone()]);
+            let is_motion = MOTIONS.contains(&key.as_str());
+            // Each `x` extends the selection by a line, as does a count
+            let is_line = key == "x";
+            if first.len() == 1
+                && binding(&first).is_some()
+                && ((is_motion && run >= 3) || (is_line && run >= 2))
+            {
+                let range = first.start..commands[i + run - 1].end;
+                let message = if is_line {
+                    format!(
+                        "`{}` selects one more line at a time",
+                        notation(&keys[range.clone()])
+                    )
+                } else {
+                    format!(
+                        "`{}` repeats the same motion",
+                        notation(&keys[range.clone()])
+                    )
+                };
+                lints.push(Lint {
+                    message,
+                    key_events: range,
+                    replacement: format!("{run}{key}"),
+                });
+            }
+            i += run.max(1);
+        }
+
+        for pair in commands.windows(2) {
+            let (command, next) = (&pair[0], &pair[1]);
+            let keys_of = |range: &Range<usize>| notation(&keys[range.clone()]);
+
+            // Leaving insert mode only to enter it again
+            if matches!(
+                roles[command.start],
+                Role::Binding {
+                    mode: Mode::Insert,
+                    ..
+                }
+            ) && keys_of(command) == "<esc>"
+                && modes[next.end - 1] == Mode::Insert
+                && let Some((_, replacement)) = REENTER_INSERT
+                    .iter()
+                    .find(|(enter, _)| *enter == keys_of(next))
+            {
+                lints.push(Lint {
+                    message: format!(
+                        "`<esc>{}` leaves insert mode only to enter it again",
+                        keys_of(next)
+                    ),
+                    key_events: command.start..next.end,
+                    replacement: (*replacement).to_string(),
+                });
+            }
+
+            let (Some((_, command_keys)), Some((mode, next_keys))) =
+                (binding(command), binding(next))
+            else {
+                continue;
+            };
+
+            // Deleting the selection and then inserting, which is what `c` does
+            if command_keys == "d" && next_keys == "i" && command.len() == 1 {
+                lints.push(Lint {
+                    message: "`di` deletes the selection and then enters insert mode".to_string(),
+                    key_events: command.start..next.end,
+                    replacement: "c".to_string(),
+                });
+            }
+
+            // Collapsing a selection which is already collapsed
+            if mode == Mode::Normal
+                && next_keys == ";"
+                && COLLAPSING.contains(&command_keys.as_str())
+            {
+                lints.push(Lint {
+                    message: format!(
+                        "`;` collapses the selection, but `{command_keys}` already did"
+                    ),
+                    key_events: next.clone(),
+                    replacement: String::new(),
+                });
+            }
+        }
+
+        lints.sort_by_key(|lint| lint.key_events.start);
+        lints
+    }
+
+    /// The `lint` as a warning, which points at its keys inside of the command block
+    pub fn lint_warning(&self, lint: &Lint) -> LintWarning {
+        let offsets = self.key_offsets();
+        let span = match (
+            offsets.get(lint.key_events.start),
+            offsets.get(lint.key_events.end.saturating_sub(1)),
+        ) {
+            (Some(first), Some(last)) => first.start..last.end,
+            // Keys that are broken across lines
+            _ => self.command_span.clone(),
+        };
+        let help = if lint.replacement.is_empty() {
+            format!(
+                "Drop `{}`",
+                notation(&self.key_events[lint.key_events.clone()])
+            )
+        } else {
+            format!("Use `{}` instead", lint.replacement)
+        };
+
+        LintWarning {
+            src: NamedSource::new(format!("{}.md", self.name), self.contents.clone()),
+            message: format!("{} in example `{}`", lint.message, self.name),
+            help,
+            span: (span.start, span.len()).into(),
+        }
+    }
+
+    /// Where each of the [`Example::key_events`] is written inside of the markdown file.
+    ///
+    /// Empty if the keys cannot be found, such as when a key is broken across lines
+    fn key_offsets(&self) -> Vec<Range<usize>> {
+        let text = &self.contents[self.command_span.clone()];
+        let value = translate::code_value(text);
+        let mut offset = self.command_span.start + (value.as_ptr().addr() - text.as_ptr().addr());
+        let mut offsets = Vec::with_capacity(self.key_events.len());
+
+        for line in value.split('\n') {
+            let mut pos = 0;
+            while pos < line.len() {
+                let Some(key) = self.key_events.get(offsets.len()) else {
+                    return Vec::new();
+                };
+                // The shortest text which is exactly this key
+                let Some(len) = (1..=line.len() - pos).find(|len| {
+                    line.get(pos..pos + len)
+                        .and_then(|text| parse_keys(text, &self.name).ok())
+                        .is_some_and(|keys| keys.as_slice() == std::slice::from_ref(key))
+                }) else {
+                    return Vec::new();
+                };
+                offsets.push(offset + pos..offset + pos + len);
+                pos += len;
+            }
+            offset += line.len() + 1;
+        }
+
+        if offsets.len() == self.key_events.len() {
+            offsets
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Example with the `command`, explained by a single step
+    fn example(command: &str) -> Example {
+        Example::from_markdown(
+            format!(
+                "# Test\n\n## Before\n\n```js\na\n```\n\n## After\n\n```js\nb\n```\n\n\
+                ## Command\n\n```\n{command}\n```\n\n1. `{}` does everything\n",
+                command.replace('\n', "")
+            ),
+            "test.md",
+            "test",
+        )
+        .unwrap()
+    }
+
+    /// Keys, message and replacement of each lint of the `command`
+    fn lints_of(command: &str) -> Vec<(String, String, String)> {
+        let example = example(command);
+        example
+            .lints()
+            .into_iter()
+            .map(|lint| {
+                (
+                    notation(&example.key_events[lint.key_events]),
+                    lint.message,
+                    lint.replacement,
+                )
+            })
+            .collect()
+    }
+
+    /// Lint with the `keys`, `message` and `replacement`
+    fn lint(keys: &str, message: &str, replacement: &str) -> (String, String, String) {
+        (
+            keys.to_string(),
+            message.to_string(),
+            replacement.to_string(),
+        )
+    }
+
+    #[test]
+    fn repeated_motion() {
+        assert_eq!(
+            lints_of("jjjjd"),
+            [lint("jjjj", "`jjjj` repeats the same motion", "4j")]
+        );
+        // Two motions are as short as a count
+        assert_eq!(lints_of("jjd"), []);
+    }
+
+    #[test]
+    fn repeated_line_selection() {
+        assert_eq!(
+            lints_of("xxd"),
+            [lint("xx", "`xx` selects one more line at a time", "2x")]
+        );
+    }
+
+    #[test]
+    fn delete_then_insert() {
+        assert_eq!(
+            lints_of("dihi<esc>"),
+            [lint(
+                "di",
+                "`di` deletes the selection and then enters insert mode",
+                "c"
+            )]
+        );
+    }
+
+    #[test]
+    fn collapse_after_collapsing_motion() {
+        assert_eq!(
+            lints_of("j;d"),
+            [lint(
+                ";",
+                "`;` collapses the selection, but `j` already did",
+                ""
+            )]
+        );
+        // `w` selects a word, which `;` collapses
+        assert_eq!(lints_of("w;d"), []);
+    }
+
+    #[test]
+    fn reenter_insert_mode() {
+        assert_eq!(
+            lints_of("ihi<esc>ab<esc>"),
+            [lint(
+                "<esc>a",
+                "`<esc>a` leaves insert mode only to enter it again",
+                "<right>"
+            )]
+        );
+        assert_eq!(
+            lints_of("ihi<esc>ib<esc>"),
+            [lint(
+                "<esc>i",
+                "`<esc>i` leaves insert mode only to enter it again",
+                ""
+            )]
+        );
+        assert_eq!(
+            lints_of("ihi<esc>ob<esc>"),
+            [lint(
+                "<esc>o",
+                "`<esc>o` leaves insert mode only to enter it again",
+                "<end><enter>"
+            )]
+        );
+        // `d` stays in normal mode
+        assert_eq!(lints_of("ihi<esc>d"), []);
+    }
+
+    #[test]
+    fn key_offsets_point_into_the_command_block() {
+        let example = example("jj\n\njjd");
+        let offsets = example.key_offsets();
+        assert_eq!(offsets.len(), example.key_events.len());
+        for (offset, key) in offsets.iter().zip(&example.key_events) {
+            assert_eq!(
+                parse_keys(&example.contents[offset.clone()], "test").unwrap(),
+                [*key]
+            );
+        }
+        // The second line starts after the blank line
+        assert!(example.contents[offsets[1].end..offsets[2].start].contains("\n\n"));
+
+        let [lint] = example.lints().try_into().unwrap();
+        let warning = example.lint_warning(&lint);
+        assert_eq!(
+            warning.span,
+            (offsets[0].start, offsets[3].end - offsets[0].start).into()
+        );
+    }
+
+    #[test]
+    fn keys_broken_across_lines_point_at_the_command_block() {
+        let example = example("ihi<es\n\nc>ab<esc>");
+        assert_eq!(example.key_offsets(), []);
+
+        let [lint] = example.lints().try_into().unwrap();
+        let span = example.command_span.clone();
+        assert_eq!(
+            example.lint_warning(&lint).span,
+            (span.start, span.len()).into()
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Which demos have been generated for each example
+//!
+//! `generate-demos` writes `manifest.json` into the generated directory,
+//! and the mdbook preprocessor reads it to embed the demos.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use miette::miette;
+use serde::{Deserialize, Serialize};
+
+#[cfg(doc)]
+use crate::Example;
+use crate::{helix_version::Version, recorder::Format, theme::Appearance};
+
+/// Name of the file in the generated directory
+const FILE: &str = "manifest.json";
+
+/// Generated files of a single example
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Entry {
+    /// Formats of the demo, each is in the generated directory as [`Example::demo_file`]
+    pub formats: Vec<Format>,
+    /// Each format has been recorded with a theme of these appearances
+    #[serde(default = "dark")]
+    pub appearances: Vec<Appearance>,
+    /// Versions of Helix which the example works with
+    #[serde(default)]
+    pub helix: Vec<Version>,
+}
+
+/// Demos recorded before there were light themes are dark
+fn dark() -> Vec<Appearance> {
+    vec![Appearance::Dark]
+}
+
+/// Generated files of every example
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Manifest {
+    /// Name of the example, to its generated files
+    pub examples: BTreeMap<String, Entry>,
+}
+
+impl Manifest {
+    /// Read the manifest from the generated directory `dir`.
+    ///
+    /// `None` if the demos have not been generated
+    pub fn read(dir: &Path) -> Option<Self> {
+        serde_json::from_str(&fs::read_to_string(dir.join(FILE)).ok()?).ok()
+    }
+
+    /// Generated demos of example `name`.
+    ///
+    /// Without a manifest, the demos are assumed to be dark `.mp4` videos
+    pub fn entry(manifest: Option<&Self>, name: &str) -> Entry {
+        manifest.map_or_else(
+            || Entry {
+                formats: vec![Format::Mp4],
+                appearances: dark(),
+                helix: Vec::new(),
+            },
+            |manifest| manifest.examples.get(name).cloned().unwrap_or_default(),
+        )
+    }
+
+    /// Write the manifest into the generated directory `dir`
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be written
+    pub fn write(&self, dir: &Path) -> miette::Result<()> {
+        fs::write(
+            dir.join(FILE),
+            serde_json::to_string_pretty(self)
+                .map_err(|err| miette!("Failed to serialize the manifest: {err}"))?,
+        )
+        .map_err(|err| miette!("Failed to write `{FILE}`: {err}"))
+    }
+}
@@ -0,0 +1,73 @@
+//! Find out what any keys do to `## Before` of an example, by typing them into Helix
+//!
+//! Unlike the recorders, nothing is captured along the way, so it is quick enough
+//! to try many different keys for the same example.
+
+use std::{fs, path::Path, thread, time::Duration};
+
+use miette::miette;
+
+use crate::{
+    helix_log, parse_example::Example, parse_helix_keys::KeyEvent, pty, terminal::Terminal,
+};
+
+/// How long to wait after each key
+const TYPING_SPEED: Duration = Duration::from_millis(20);
+
+/// Type the `keys` into Helix of the `terminal`, editing `## Before` of the `example`
+/// inside of `dir`, and return the contents of the file once they have been typed.
+///
+/// The Helix config must already be generated in `dir`, see [`crate::generate_helix_config`]
+///
+/// # Errors
+///
+/// If Helix cannot be started, or shows an error or a warning in the statusline
+pub fn run(
+    example: &Example,
+    keys: &[KeyEvent],
+    dir: &Path,
+    terminal: &Terminal,
+) -> miette::Result<String> {
+    let file = dir.join(format!("{}.{}", example.name, example.language));
+    fs::write(&file, &example.before)
+        .map_err(|err| miette!("Failed to write `{}`: {err}", file.display()))?;
+    let log_file = dir.join(example.log_file());
+    fs::write(&log_file, "")
+        .map_err(|err| miette!("Failed to create `{}`: {err}", log_file.display()))?;
+
+    let session = pty::Session::spawn(example, dir, terminal)?;
+    // Wait until Helix has drawn the file
+    session.wait_idle(Duration::from_millis(500), Duration::from_secs(10));
+
+    for key in keys {
+        session.press(key)?;
+        thread::sleep(TYPING_SPEED);
+    }
+
+    // Leave insert mode or close a prompt, if the keys end in one
+    session.write(b"\x1b")?;
+    thread::sleep(TYPING_SPEED);
+    session.write(b":write!\r")?;
+    session.wait_idle(Duration::from_millis(100), Duration::from_secs(5));
+    session.quit()?;
+
+    if let Some(message) = helix_log::messages(&helix_log::read(example, dir, 0)).first() {
+        return Err(helix_log::error(example, message, None));
+    }
+
+    fs::read_to_string(&file).map_err(|err| miette!("Failed to read `{}`: {err}", file.display()))
+}
+
+/// Whether the `keys` turn `## Before` of the `example` into `## After`, see [`run`]
+///
+/// # Errors
+///
+/// If Helix cannot be started, or shows an error or a warning in the statusline
+pub fn solves(
+    example: &Example,
+    keys: &[KeyEvent],
+    dir: &Path,
+    terminal: &Terminal,
+) -> miette::Result<bool> {
+    run(example, keys, dir, terminal).map(|result| result.trim() == example.after.trim())
+}
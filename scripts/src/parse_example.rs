@@ -1,16 +1,20 @@
 //! Ensure that each markdown file corresponds to the expected structure
 
-use std::{collections::HashSet, fs, path::Path};
+use std::{
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
 
 use markdown::{
     ParseOptions,
-    mdast::{Code, Emphasis, Heading, InlineCode, Link, List, Node, Paragraph, Strong, Text},
+    mdast::{Code, Emphasis, Heading, Html, InlineCode, Link, List, Node, Paragraph, Strong, Text},
     unist::{Point, Position},
 };
-use miette::{Context as _, NamedSource, SourceSpan, miette};
+use miette::{Context as _, NamedSource, SourceSpan, ensure, miette};
 use rayon::{iter::ParallelIterator as _, slice::ParallelSlice as _};
 
-use crate::parse_helix_keys::KeyEvent;
+use crate::{helix_version::Version, parse_helix_keys::KeyEvent, score};
 
 /// The current element that we are expecting.
 #[derive(Clone)]
@@ -85,6 +89,19 @@ enum Expecting {
     ListCommand(Position),
     /// The required structure was met.
     Finished,
+    /// Code block after an optional `## Alternative` heading, once the structure was met.
+    /// It may be followed by the score of the keys
+    ///
+    /// ````md
+    /// ## Alternative
+    ///
+    /// ```
+    /// %<A-s>ms"<A-J>i,<esc>xms ms[
+    /// ```
+    ///
+    /// _Score: 13 keystrokes, 8 commands, 1 inserted character_
+    /// ````
+    CodeAlternative(Position),
 }
 
 impl Expecting {
@@ -100,6 +117,7 @@ impl Expecting {
             Self::CodeCommand(_) => Self::CodeCommand(pos),
             Self::ListCommand(_) => Self::ListCommand(pos),
             Self::Finished => Self::Finished,
+            Self::CodeAlternative(_) => Self::CodeAlternative(pos),
         }
     }
 
@@ -119,6 +137,7 @@ impl Expecting {
             Self::TitleCommand(pos) => (pos, "expected heading `## Title`"),
             Self::CodeCommand(pos) => (pos, "expected code block after `## Title`"),
             Self::ListCommand(pos) => (pos, "expected numbered list describing each command"),
+            Self::CodeAlternative(pos) => (pos, "expected code block after `## Alternative`"),
             Self::Finished => return None,
         })
     }
@@ -135,7 +154,7 @@ impl Expecting {
             Self::CodeAfter(_) => Self::TitleCommand(pos),
             Self::TitleCommand(_) => Self::CodeCommand(pos),
             Self::CodeCommand(_) => Self::ListCommand(pos),
-            Self::ListCommand(_) | Self::Finished => Self::Finished,
+            Self::ListCommand(_) | Self::Finished | Self::CodeAlternative(_) => Self::Finished,
         };
     }
 }
@@ -165,6 +184,11 @@ pub struct Example {
     pub title: String,
     /// Description of the example, after the 1st heading `# ...`
     pub description: Option<String>,
+    /// Tags of the example, from a `<!-- tags: ... -->` comment before `## Before`
+    pub tags: Vec<String>,
+    /// Oldest version of Helix that the example works with, from a
+    /// `<!-- minimum-helix-version: ... -->` comment before `## Before`
+    pub minimum_helix_version: Option<Version>,
     /// Contents of the file before the `command`
     pub before: String,
     /// Contents of the file after the `command`
@@ -173,40 +197,86 @@ pub struct Example {
     pub language: String,
     /// Command to go from `before` -> `after`
     pub command: String,
+    /// Byte range of the code block after `## Command` in the markdown file, with its fences
+    pub command_span: Range<usize>,
     /// Parsed `command` into a structure that can be converted into a `.tape` file
     pub key_events: Vec<KeyEvent>,
+    /// Each item of the numbered list describing the `command`
+    pub steps: Vec<Step>,
+    /// Other keys which turn `before` into `after`, from the `## Alternative` sections
+    /// after the list
+    pub alternatives: Vec<Alternative>,
+}
+
+/// Keys from an `## Alternative` section, which also turn `## Before` into `## After`
+#[derive(Default, Debug, Clone)]
+pub struct Alternative {
+    /// The keys, in the notation of the examples
+    pub command: String,
+    /// Byte range of the code block in the markdown file, with its fences
+    pub command_span: Range<usize>,
+    /// Parsed `command`
+    pub key_events: Vec<KeyEvent>,
+    /// Byte range of the `_Score: ..._` after the code block, if it has one
+    pub score_span: Option<Range<usize>>,
+}
+
+/// A single item in the numbered list which describes the `command`
+#[derive(Default, Debug, Clone)]
+pub struct Step {
+    /// All inline code in this item, concatenated
+    pub keys: String,
+    /// Byte range of each inline code and code block in this item, with its backticks
+    pub code_spans: Vec<Range<usize>>,
+    /// The `Example::key_events` which this item describes
+    pub key_events: Range<usize>,
+    /// Byte offset in the markdown file where this item ends
+    pub end_offset: usize,
+    /// Column at which the contents of this item start, so that
+    /// we can insert more content into the item
+    pub indent: usize,
+    /// Markdown of this item, without the number
+    pub text: String,
 }
 
 impl Example {
-    /// Reads multiple examples from the `root` directory.
+    /// Paths to the markdown files of all examples in the `root` directory.
+    ///
+    /// # Errors
     ///
-    /// If `filter` is empty, parse all of the examples from `root`.
-    /// Otherwise, parses all of the examples in `filter` that are available in the `root`.
-    pub fn parse_all(root: &Path, filter: &HashSet<String>) -> miette::Result<Vec<Self>> {
-        fs::read_dir(root)
+    /// If `root` cannot be read, or it has no examples such as when it is
+    /// relative to another directory than the current one
+    pub fn paths(root: &Path) -> miette::Result<Vec<PathBuf>> {
+        let paths = fs::read_dir(root)
             .map_err(|err| miette!("failed to read {root}: {err}", root = root.display()))?
             .flatten()
             .filter(|entry| {
-                let stem = entry.path();
-                let stem = stem.file_stem().and_then(|stem| stem.to_str()).unwrap();
+                let path = entry.path();
+                let stem = path.file_stem().and_then(|stem| stem.to_str());
 
                 entry.file_type().is_ok_and(|ft| ft.is_file())
+                    && path.extension().is_some_and(|ext| ext == "md")
                     // fully ignore these files, as we auto-generate them in a special way
-                    && stem != "SUMMARY"
-                    && stem != "introduction"
-                    && if filter.is_empty() {
-                        // include everything if no filter specified
-                        true
-                    } else {
-                        // include only the stuff specified in the filter
-                        filter.contains(stem)
-                    }
+                    && stem.is_some_and(|stem| stem != "SUMMARY" && stem != "introduction")
+                    // hidden files, and so the names of our own directories, are not examples
+                    && stem.is_some_and(|stem| !stem.starts_with('.'))
             })
-            .map(|entry| Self::parse(&entry.path()))
-            .collect()
+            .map(|entry| entry.path())
+            .collect::<Vec<_>>();
+
+        ensure!(
+            !paths.is_empty(),
+            "there are no examples in `{}`, give the directory with the examples with `--root`",
+            root.display()
+        );
+        Ok(paths)
     }
 
     /// Try to parse path of the given markdown file
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be read, or it is not a valid example
     pub fn parse(path: &Path) -> miette::Result<Self> {
         let markdown = fs::read_to_string(path)
             .map_err(|err| miette!("failed to read path {}: {err}", path.display()))?;
@@ -220,6 +290,24 @@ impl Example {
             .and_then(|stem| stem.to_str())
             .context("missing filename")?;
 
+        Self::from_markdown(markdown, file_name, file_stem)
+    }
+
+    /// Parse contents of a markdown file with the given `file_name`
+    ///
+    /// # Errors
+    ///
+    /// If the markdown does not have the structure of an example, which is described
+    /// in the `README.md`. Or if the keys of the command cannot be parsed
+    ///
+    /// # Panics
+    ///
+    /// Never, as parsing markdown only fails for MDX
+    pub fn from_markdown(
+        markdown: String,
+        file_name: &str,
+        file_stem: &str,
+    ) -> miette::Result<Self> {
         markdown::to_mdast(&markdown, &ParseOptions::default())
             .unwrap()
             .children()
@@ -266,7 +354,7 @@ impl Example {
                                     return Err(expected_err_with_pos(position));
                                 };
 
-                                example.title = value.to_string();
+                                example.title.clone_from(value);
 
                                 expecting.next(position.clone().unwrap());
                             }
@@ -305,7 +393,7 @@ impl Example {
                                                 "[{}]({url})",
                                                 inline_mdast_into_md_string(children)
                                             ),
-                                            Node::Text(Text { value, .. }) => value.to_string(),
+                                            Node::Text(Text { value, .. }) => value.clone(),
                                             Node::InlineCode(InlineCode { value, .. }) => {
                                                 format!("`{value}`")
                                             }
@@ -321,6 +409,38 @@ impl Example {
                                 }
 
                                 example.description = Some(inline_mdast_into_md_string(children));
+                            // optional tags, in a comment that is not visible in the book
+                            //
+                            // <!-- tags: regex, multiple cursors -->
+                            } else if let Node::Html(Html { value, .. }) = child
+                                && let Some(tags) = value
+                                    .trim()
+                                    .strip_prefix("<!--")
+                                    .and_then(|comment| comment.strip_suffix("-->"))
+                                    .and_then(|comment| comment.trim().strip_prefix("tags:"))
+                            {
+                                example.tags = tags
+                                    .split(',')
+                                    .map(str::trim)
+                                    .filter(|tag| !tag.is_empty())
+                                    .map(ToString::to_string)
+                                    .collect();
+                            // optional oldest version of Helix that the example works with
+                            //
+                            // <!-- minimum-helix-version: 24.07 -->
+                            } else if let Node::Html(Html { value, position }) = child
+                                && let Some(version) = value
+                                    .trim()
+                                    .strip_prefix("<!--")
+                                    .and_then(|comment| comment.strip_suffix("-->"))
+                                    .and_then(|comment| {
+                                        comment.trim().strip_prefix("minimum-helix-version:")
+                                    })
+                            {
+                                example.minimum_helix_version =
+                                    Some(version.parse().map_err(|err: miette::Report| {
+                                        (position.clone().unwrap(), err.to_string())
+                                    })?);
                             }
                         }
                         Expecting::CodeBefore(_) => {
@@ -329,7 +449,7 @@ impl Example {
                             }) = child
                             {
                                 example.before = if value.ends_with('\n') {
-                                    value.to_string()
+                                    value.clone()
                                 } else {
                                     format!("{value}\n")
                                 };
@@ -364,7 +484,7 @@ impl Example {
                                 ..
                             }) = child
                             {
-                                example.after = value.to_string();
+                                example.after.clone_from(value);
                                 example.language = lang.clone().unwrap_or_default();
 
                                 expecting.next(position.clone().unwrap());
@@ -440,6 +560,7 @@ impl Example {
                                 }
 
                                 example.command = value.replace('\n', "");
+                                example.command_span = position.start.offset..position.end.offset;
 
                                 expecting.next(position);
                             }
@@ -454,13 +575,29 @@ impl Example {
                             {
                                 let mut concatenated_inline_code = String::new();
                                 for child in children {
-                                    if let Node::Code(Code { value, .. }) = child {
-                                        concatenated_inline_code.push_str(value.trim());
+                                    // inline code of the current List Item
+                                    let mut step_keys = String::new();
+                                    let mut code_spans = Vec::new();
+                                    let span = |position: &Option<Position>| {
+                                        position.as_ref().map_or(0..0, |position| {
+                                            position.start.offset..position.end.offset
+                                        })
+                                    };
+                                    if let Node::Code(Code {
+                                        value, position, ..
+                                    }) = child
+                                    {
+                                        step_keys.push_str(value.trim());
+                                        code_spans.push(span(position));
                                     } else if let Some(children) = child.children() {
                                         for child in children {
                                             // each child in the List Item
-                                            if let Node::Code(Code { value, .. }) = child {
-                                                concatenated_inline_code.push_str(value.trim());
+                                            if let Node::Code(Code {
+                                                value, position, ..
+                                            }) = child
+                                            {
+                                                step_keys.push_str(value.trim());
+                                                code_spans.push(span(position));
                                             } else {
                                                 let inline_code_concatenated = child
                                                     .children()
@@ -471,9 +608,10 @@ impl Example {
                                                         // only care about `InlineCode`
                                                         if let Node::InlineCode(InlineCode {
                                                             value,
-                                                            ..
+                                                            position,
                                                         }) = child
                                                         {
+                                                            code_spans.push(span(position));
                                                             Some(value)
                                                         } else {
                                                             None
@@ -482,11 +620,34 @@ impl Example {
                                                     .fold(String::new(), |total, inline_code| {
                                                         total + inline_code
                                                     });
-                                                concatenated_inline_code
-                                                    .push_str(&inline_code_concatenated);
+                                                step_keys.push_str(&inline_code_concatenated);
                                             }
                                         }
                                     }
+                                    concatenated_inline_code.push_str(&step_keys);
+
+                                    let item_position = child.position().unwrap();
+                                    let contents_position = child
+                                        .children()
+                                        .and_then(|children| children.first())
+                                        .and_then(Node::position);
+                                    example.steps.push(Step {
+                                        keys: step_keys,
+                                        code_spans,
+                                        key_events: 0..0,
+                                        end_offset: item_position.end.offset,
+                                        indent: contents_position
+                                            .map_or(0, |position| position.start.column - 1),
+                                        text: contents_position
+                                            .and_then(|position| {
+                                                markdown.get(
+                                                    position.start.offset..item_position.end.offset,
+                                                )
+                                            })
+                                            .unwrap_or_default()
+                                            .trim()
+                                            .to_string(),
+                                    });
                                 }
 
                                 if concatenated_inline_code != example.command {
@@ -509,7 +670,47 @@ impl Example {
                                 expecting.next(position.clone().unwrap());
                             }
                         }
-                        Expecting::Finished => (),
+                        Expecting::Finished => {
+                            if let Node::Heading(Heading {
+                                children,
+                                depth: 2,
+                                position,
+                            }) = child
+                                && let Some(Node::Text(Text { value, .. })) = children.first()
+                                && value == "Alternative"
+                            {
+                                expecting = Expecting::CodeAlternative(position.clone().unwrap());
+                            // the score of the keys of the alternative above
+                            //
+                            // _Score: 13 keystrokes, 8 commands, 1 inserted character_
+                            } else if let Node::Paragraph(Paragraph { children, position }) = child
+                                && let [Node::Emphasis(Emphasis { children, .. })] =
+                                    children.as_slice()
+                                && let Some(Node::Text(Text { value, .. })) = children.first()
+                                && value.starts_with("Score:")
+                                && let Some(alternative) = example.alternatives.last_mut()
+                                && alternative.score_span.is_none()
+                            {
+                                let position = position.clone().unwrap();
+                                alternative.score_span =
+                                    Some(position.start.offset..position.end.offset);
+                            }
+                        }
+                        Expecting::CodeAlternative(_) => {
+                            if let Node::Code(Code {
+                                value, position, ..
+                            }) = child
+                            {
+                                let position = position.clone().unwrap();
+                                example.alternatives.push(Alternative {
+                                    command: value.replace('\n', ""),
+                                    command_span: position.start.offset..position.end.offset,
+                                    ..Alternative::default()
+                                });
+
+                                expecting.next(position);
+                            }
+                        }
                     }
                     Ok((expecting, example))
                 },
@@ -532,6 +733,42 @@ impl Example {
             .and_then(|mut example| {
                 example.key_events =
                     crate::parse_helix_keys::parse_keys(&example.command, file_stem)?;
+
+                // Each step explains a consecutive range of key events
+                let mut start = 0;
+                for step in &mut example.steps {
+                    let end =
+                        start + crate::parse_helix_keys::parse_keys(&step.keys, file_stem)?.len();
+                    step.key_events = start..end;
+                    start = end;
+                }
+                ensure!(
+                    start == example.key_events.len(),
+                    "each item in the explanation list of `{file_name}` \
+                    must only contain complete keys"
+                );
+
+                // The score written beneath an alternative must be the one of its keys
+                for alternative in &mut example.alternatives {
+                    alternative.key_events =
+                        crate::parse_helix_keys::parse_keys(&alternative.command, file_stem)?;
+                    let expected = format!("_Score: {}_", score::score(&alternative.key_events));
+                    if let Some(span) = alternative.score_span.clone()
+                        && markdown[span.clone()].trim() != expected
+                    {
+                        return Err(InvalidStructure {
+                            src: NamedSource::new(file_name, markdown.clone()),
+                            reason: format!(
+                                "the score of alternative `{}` is out of date, \
+                                it should be {expected}",
+                                alternative.command
+                            ),
+                            span: (span.start, span.len()).into(),
+                        }
+                        .into());
+                    }
+                }
+
                 example.name = file_stem.to_string();
                 example.contents = markdown;
                 Ok(example)
@@ -4,7 +4,7 @@
 //!
 //! Includes some modifications.
 
-//! Convert the Helix `KeyCode`s into something that [`vhs`](vhs) can understand
+//! Convert the Helix `KeyCode`s into something that [`vhs`][vhs] can understand
 //!
 //! [vhs]: https://github.com/charmbracelet/vhs
 
@@ -386,6 +386,7 @@ use bitflags::bitflags;
 use miette::{Diagnostic, NamedSource, SourceSpan};
 use thiserror::Error;
 
+/// The keys are not in valid Helix notation
 #[derive(Error, Diagnostic, Debug)]
 #[error("Could not parse the keys")]
 pub struct ParseKeysError {
@@ -396,6 +397,13 @@ pub struct ParseKeysError {
     span: SourceSpan,
 }
 
+/// Parse a sequence of keys in Helix notation such as `%<alt-s>ms"`.
+///
+/// `filename` is the name of the file that contains the keys, for error messages
+///
+/// # Errors
+///
+/// If any of the keys is invalid
 pub fn parse_keys(keys_str: &str, filename: &str) -> Result<Vec<KeyEvent>, ParseKeysError> {
     let mut keys_res: Result<_, ParseKeysError> = Ok(Vec::new());
     let mut i = 0;
@@ -436,6 +444,17 @@ pub fn parse_keys(keys_str: &str, filename: &str) -> Result<Vec<KeyEvent>, Parse
 }
 
 impl KeyEvent {
+    /// Parse a single key such as `alt-s` (without the angle brackets) or `%`.
+    ///
+    /// `keys_str` is the entire key sequence which contains this key, in `filename`
+    ///
+    /// # Errors
+    ///
+    /// If the key or its modifiers are invalid
+    ///
+    /// # Panics
+    ///
+    /// Never, a key with a single character always has that character
     pub fn from_str(s: &str, filename: &str, keys_str: &str) -> Result<Self, ParseKeysError> {
         let mut tokens: Vec<_> = s.split('-').collect();
         let mut code = match tokens.pop().ok_or_else(|| ParseKeysError {
@@ -603,13 +622,95 @@ impl KeyEvent {
 
         Ok(Self { code, modifiers })
     }
+
+    /// The key in Helix notation, as it is written in the examples, such as `%` or `<alt-s>`.
+    ///
+    /// [`parse_keys`] turns it back into this key
+    pub fn notation(&self) -> String {
+        let name = match self.code {
+            KeyCode::Backspace => keys::BACKSPACE,
+            KeyCode::Enter => keys::ENTER,
+            KeyCode::Left => keys::LEFT,
+            KeyCode::Right => keys::RIGHT,
+            KeyCode::Up => keys::UP,
+            KeyCode::Down => keys::DOWN,
+            KeyCode::Home => keys::HOME,
+            KeyCode::End => keys::END,
+            KeyCode::PageUp => keys::PAGEUP,
+            KeyCode::PageDown => keys::PAGEDOWN,
+            KeyCode::Tab => keys::TAB,
+            KeyCode::Delete => keys::DELETE,
+            KeyCode::Insert => keys::INSERT,
+            KeyCode::Null => keys::NULL,
+            KeyCode::Esc => keys::ESC,
+            KeyCode::Char(' ') => keys::SPACE,
+            KeyCode::Char('<') => keys::LESS_THAN,
+            KeyCode::Char('-') if !self.modifiers.is_empty() => keys::MINUS,
+            KeyCode::Char('>') if !self.modifiers.is_empty() => keys::GREATER_THAN,
+            KeyCode::Char(ch) if self.modifiers.is_empty() => return ch.to_string(),
+            KeyCode::Char(ch) => &ch.to_string(),
+            KeyCode::F(n) => &format!("F{n}"),
+            KeyCode::CapsLock => keys::CAPS_LOCK,
+            KeyCode::ScrollLock => keys::SCROLL_LOCK,
+            KeyCode::NumLock => keys::NUM_LOCK,
+            KeyCode::PrintScreen => keys::PRINT_SCREEN,
+            KeyCode::Pause => keys::PAUSE,
+            KeyCode::Menu => keys::MENU,
+            KeyCode::KeypadBegin => keys::KEYPAD_BEGIN,
+            KeyCode::Media(media) => match media {
+                MediaKeyCode::Play => keys::PLAY,
+                MediaKeyCode::Pause => keys::PAUSE_MEDIA,
+                MediaKeyCode::PlayPause => keys::PLAY_PAUSE,
+                MediaKeyCode::Reverse => keys::REVERSE,
+                MediaKeyCode::Stop => keys::STOP,
+                MediaKeyCode::FastForward => keys::FAST_FORWARD,
+                MediaKeyCode::Rewind => keys::REWIND,
+                MediaKeyCode::TrackNext => keys::TRACK_NEXT,
+                MediaKeyCode::TrackPrevious => keys::TRACK_PREVIOUS,
+                MediaKeyCode::Record => keys::RECORD,
+                MediaKeyCode::LowerVolume => keys::LOWER_VOLUME,
+                MediaKeyCode::RaiseVolume => keys::RAISE_VOLUME,
+                MediaKeyCode::MuteVolume => keys::MUTE_VOLUME,
+            },
+            KeyCode::Modifier(modifier) => match modifier {
+                ModifierKeyCode::LeftShift => keys::LEFT_SHIFT,
+                ModifierKeyCode::LeftControl => keys::LEFT_CONTROL,
+                ModifierKeyCode::LeftAlt => keys::LEFT_ALT,
+                ModifierKeyCode::LeftSuper => keys::LEFT_SUPER,
+                ModifierKeyCode::LeftHyper => keys::LEFT_HYPER,
+                ModifierKeyCode::LeftMeta => keys::LEFT_META,
+                ModifierKeyCode::RightShift => keys::RIGHT_SHIFT,
+                ModifierKeyCode::RightControl => keys::RIGHT_CONTROL,
+                ModifierKeyCode::RightAlt => keys::RIGHT_ALT,
+                ModifierKeyCode::RightSuper => keys::RIGHT_SUPER,
+                ModifierKeyCode::RightHyper => keys::RIGHT_HYPER,
+                ModifierKeyCode::RightMeta => keys::RIGHT_META,
+                ModifierKeyCode::IsoLevel3Shift => keys::ISO_LEVEL_3_SHIFT,
+                ModifierKeyCode::IsoLevel5Shift => keys::ISO_LEVEL_5_SHIFT,
+            },
+        };
+
+        let modifiers = [
+            (KeyModifiers::CONTROL, "ctrl-"),
+            (KeyModifiers::ALT, "alt-"),
+            (KeyModifiers::SHIFT, "shift-"),
+            (KeyModifiers::SUPER, "Meta-"),
+        ]
+        .into_iter()
+        .filter_map(|(modifier, prefix)| self.modifiers.contains(modifier).then_some(prefix))
+        .collect::<String>();
+
+        format!("<{modifiers}{name}>")
+    }
 }
 
 /// Represents a key event.
 // We use a newtype here because we want to customize Deserialize and Display.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
 pub struct KeyEvent {
+    /// The key which was pressed
     pub code: KeyCode,
+    /// Modifiers held while the key was pressed
     pub modifiers: KeyModifiers,
     // TODO: crossterm now supports kind & state if terminal supports kitty's extended protocol
 }
@@ -743,15 +844,20 @@ bitflags! {
     /// Represents key modifiers (shift, control, alt).
     #[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone, Copy, Hash)]
     pub struct KeyModifiers: u8 {
+        /// Shift key
         const SHIFT = 0b0000_0001;
+        /// Control key
         const CONTROL = 0b0000_0010;
+        /// Alt key
         const ALT = 0b0000_0100;
+        /// Super key, also known as Meta, Cmd or Win
         const SUPER = 0b0000_1000;
+        /// No modifiers
         const NONE = 0b0000_0000;
     }
 }
 
-pub mod keys {
+mod keys {
     pub const BACKSPACE: &str = "backspace";
     // NOTE: In Helix, it is "ret"
     pub const ENTER: &str = "enter";
@@ -807,3 +913,81 @@ pub mod keys {
     pub const ISO_LEVEL_3_SHIFT: &str = "isolevel3shift";
     pub const ISO_LEVEL_5_SHIFT: &str = "isolevel5shift";
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `notation` is the notation of a single key, which is written the same way again
+    fn round_trip(notation: &str) {
+        let keys = parse_keys(notation, "test").unwrap();
+        let [key] = keys.as_slice() else {
+            panic!("`{notation}` is not a single key: {keys:?}");
+        };
+        assert_eq!(key.notation(), notation);
+        assert_eq!(parse_keys(&key.notation(), "test").unwrap(), [*key]);
+    }
+
+    #[test]
+    fn characters_round_trip() {
+        for notation in [
+            "a", "A", "%", ";", "-", ">", "`", "~", "é", "<lt>", "<space>",
+        ] {
+            round_trip(notation);
+        }
+    }
+
+    #[test]
+    fn special_keys_round_trip() {
+        for notation in [
+            "<enter>",
+            "<tab>",
+            "<backspace>",
+            "<esc>",
+            "<left>",
+            "<right>",
+            "<up>",
+            "<down>",
+            "<home>",
+            "<end>",
+            "<pageup>",
+            "<pagedown>",
+            "<ins>",
+            "<del>",
+            "<F1>",
+            "<F12>",
+        ] {
+            round_trip(notation);
+        }
+    }
+
+    #[test]
+    fn modified_keys_round_trip() {
+        for notation in [
+            "<ctrl-w>",
+            "<alt-s>",
+            "<alt-S>",
+            "<ctrl-alt-x>",
+            "<alt-minus>",
+            "<ctrl-gt>",
+            "<alt-lt>",
+            "<ctrl-space>",
+            "<alt-enter>",
+            "<shift-tab>",
+            "<ctrl-shift-left>",
+            "<Meta-a>",
+        ] {
+            round_trip(notation);
+        }
+    }
+
+    #[test]
+    fn shift_of_a_character_is_its_uppercase() {
+        let [key] = parse_keys("<ctrl-shift-r>", "test")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(key.notation(), "<ctrl-R>");
+        assert_eq!(parse_keys("<ctrl-R>", "test").unwrap(), [key]);
+    }
+}
@@ -0,0 +1,162 @@
+//! Where the examples are, and where the generated files go
+
+use std::{
+    fs,
+    path::{self, Component, Path, PathBuf},
+};
+
+use miette::{ensure, miette};
+
+/// File which marks a directory as created by helix-golf, so that it may be cleared
+const MARKER: &str = ".helix-golf";
+
+/// Directory inside of the generated and snapshot directories with a directory for each
+/// other version of Helix. Examples cannot start with a `.`, so none has the same name
+const HELIX_VERSIONS: &str = ".helix-versions";
+
+/// Location of the examples, and of the files that we generate
+#[derive(Clone, Debug)]
+pub struct Paths {
+    /// Source directory for the mdbook content files
+    pub root: PathBuf,
+    /// Directory where we place all of the generated files
+    pub generated: PathBuf,
+    /// Directory with the state of the buffer after each step of every example.
+    ///
+    /// Unlike `generated`, it is kept between runs
+    pub snapshots: PathBuf,
+}
+
+impl Paths {
+    /// Use `root` as the source directory, and place the generated files in `out_dir`
+    pub fn new(root: &Path, out_dir: Option<&Path>) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            generated: out_dir.map_or_else(|| root.join("generated"), Path::to_path_buf),
+            snapshots: root.join("snapshots"),
+        }
+    }
+
+    /// Where the files of the Helix `version` go, when it is not the one
+    /// which records the demos
+    #[must_use]
+    pub fn helix_version(&self, version: &str) -> Self {
+        Self {
+            root: self.root.clone(),
+            generated: self.generated.join(HELIX_VERSIONS).join(version),
+            snapshots: self.snapshots.join(HELIX_VERSIONS).join(version),
+        }
+    }
+
+    /// URL of the generated directory, relative to the root of the book
+    ///
+    /// # Errors
+    ///
+    /// If the generated directory is not inside of the root, so the book cannot link to it
+    pub fn generated_url(&self) -> miette::Result<String> {
+        let root = resolve(&self.root);
+        let generated = resolve(&self.generated);
+
+        Ok(generated
+            .strip_prefix(&root)
+            .map_err(|_| {
+                miette!(
+                    "the generated directory `{}` is not inside of the source directory \
+                    of the book `{}`, so the book cannot link to the generated files",
+                    self.generated.display(),
+                    self.root.display()
+                )
+            })?
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"))
+    }
+
+    /// Remove the files of the previous run from the generated directory, and create it
+    /// if it does not exist. With `examples`, only the files of the examples with
+    /// these names are removed, so the demos of the other examples are kept.
+    ///
+    /// Only a directory which helix-golf has created before is cleared, so that an
+    /// `--out-dir` such as `.` does not delete the files of the reader
+    ///
+    /// # Errors
+    ///
+    /// If the generated directory has other files, or cannot be cleared
+    pub fn clean_generated(&self, examples: Option<&[String]>) -> miette::Result<()> {
+        let dir = &self.generated;
+        let is_ours = dir.join(MARKER).exists()
+            || resolve(dir) == resolve(&self.root.join("generated"))
+            || fs::read_dir(dir).map_or(true, |mut entries| entries.next().is_none());
+        ensure!(
+            is_ours,
+            "refusing to clear `{}`, as it is not empty and was not created by helix-golf. \
+            Give an `--out-dir` which is empty or does not exist yet",
+            dir.display()
+        );
+
+        match examples {
+            Some(examples) => {
+                for name in examples {
+                    remove_example_files(dir, name, true).map_err(|err| {
+                        miette!("failed removing the generated files of `{name}`: {err}")
+                    })?;
+                }
+            }
+            None if dir.exists() => fs::remove_dir_all(dir)
+                .map_err(|err| miette!("failed cleaning the generated directory: {err}"))?,
+            None => {}
+        }
+        fs::create_dir_all(dir)
+            .and_then(|()| fs::write(dir.join(MARKER), ""))
+            .map_err(|err| miette!("failed creating the generated directory: {err}"))
+    }
+}
+
+/// Remove the files of the example `name` inside of `dir`, such as `name.mp4`, and in the
+/// directories below it, such as `snapshots/name`. The files of the other examples are kept.
+///
+/// The directories directly inside of the generated directory, its `top`, are never removed
+/// entirely, even if an example has the same name
+fn remove_example_files(dir: &Path, name: &str, top: bool) -> std::io::Result<()> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        let is_dir = entry.file_type()?.is_dir();
+
+        if file_name.starts_with(&format!("{name}.")) && !is_dir {
+            fs::remove_file(&path)?;
+        } else if file_name == name && !top {
+            if is_dir {
+                fs::remove_dir_all(&path)?;
+            } else {
+                fs::remove_file(&path)?;
+            }
+        } else if is_dir {
+            remove_example_files(&path, name, false)?;
+        }
+    }
+    Ok(())
+}
+
+/// The `path` as an absolute path without `.` and `..`, relative to the current directory
+fn resolve(path: &Path) -> PathBuf {
+    path::absolute(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .components()
+        .fold(PathBuf::new(), |mut resolved, component| {
+            match component {
+                Component::ParentDir => {
+                    resolved.pop();
+                }
+                Component::CurDir => {}
+                component => resolved.push(component),
+            }
+            resolved
+        })
+}
@@ -0,0 +1,96 @@
+//! Practise an example by editing `## Before` in Helix, and check the result against `## After`
+//!
+//! The keys are recorded as they are typed, so they can be scored against the command
+//! of the example. The keys which save the file and quit Helix are not counted.
+
+use std::{env, fs};
+
+use miette::miette;
+
+use crate::{
+    generate_helix_config,
+    helix_mode::{Mode, ModeTracker, Role},
+    helix_version::Helix,
+    parse_example::Example,
+    parse_helix_keys::{KeyCode, KeyEvent},
+    pty,
+    score::{self, Score},
+};
+
+/// What the reader did while practising an example
+#[derive(Clone, Debug)]
+pub struct Attempt {
+    /// The keys which were typed, without those which saved the file and quit Helix
+    pub keys: Vec<KeyEvent>,
+    /// Score of the keys
+    pub score: Score,
+    /// Score of the command of the example
+    pub reference: Score,
+    /// The file once Helix exited
+    pub result: String,
+    /// Whether the result is `## After`
+    pub solves: bool,
+}
+
+/// Open `## Before` of the `example` in the Helix of `helix`, in a temporary directory,
+/// and let the reader edit it until Helix exits.
+///
+/// # Errors
+///
+/// If the file cannot be written, or Helix cannot be started
+pub fn practise(example: &Example, helix: &Helix) -> miette::Result<Attempt> {
+    let dir = env::temp_dir().join("helix-golf").join(&example.name);
+    fs::create_dir_all(&dir)
+        .map_err(|err| miette!("Failed to create `{}`: {err}", dir.display()))?;
+    generate_helix_config::generate_default_keys(&dir)?;
+
+    let file = dir.join(format!("{}.{}", example.name, example.language));
+    fs::write(&file, &example.before)
+        .map_err(|err| miette!("Failed to write `{}`: {err}", file.display()))?;
+    let log_file = dir.join(example.log_file());
+    fs::write(&log_file, "")
+        .map_err(|err| miette!("Failed to create `{}`: {err}", log_file.display()))?;
+
+    let typed = pty::interact(example, &dir, helix)?;
+    let keys = without_exit(pty::decode(&typed));
+
+    let result = fs::read_to_string(&file)
+        .map_err(|err| miette!("Failed to read `{}`: {err}", file.display()))?;
+
+    Ok(Attempt {
+        score: score::score(&keys),
+        reference: example.score(),
+        solves: result.trim() == example.after.trim(),
+        result,
+        keys,
+    })
+}
+
+/// The `keys` before the last command typed into the `:` prompt, which quit Helix,
+/// and without the escapes which returned to normal mode for it
+fn without_exit(mut keys: Vec<KeyEvent>) -> Vec<KeyEvent> {
+    let mut tracker = ModeTracker::default();
+    let mut exit = None;
+    for (i, key) in keys.iter().enumerate() {
+        if key.code == KeyCode::Char(':')
+            && matches!(
+                tracker.role(key),
+                Role::Binding {
+                    mode: Mode::Normal | Mode::Select,
+                    continues: false,
+                }
+            )
+        {
+            exit = Some(i);
+        }
+        tracker.feed(key);
+    }
+
+    if let Some(exit) = exit {
+        keys.truncate(exit);
+    }
+    while keys.last().is_some_and(|key| key.code == KeyCode::Esc) {
+        keys.pop();
+    }
+    keys
+}
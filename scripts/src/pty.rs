@@ -0,0 +1,815 @@
+//! Run Helix inside of a pseudo-terminal and type the keys of an example into it.
+//!
+//! Used by the recorders which do not rely on `vhs`
+
+use std::{
+    io::{self, Read as _, Write},
+    os::fd::{AsFd as _, AsRawFd as _},
+    path::Path,
+    sync::{
+        Arc, Mutex, MutexGuard,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use miette::{ensure, miette};
+use nix::{
+    errno::Errno,
+    poll::{PollFd, PollFlags, poll},
+    sys::termios::{self, SetArg, Termios},
+    unistd,
+};
+use portable_pty::{Child, CommandBuilder, MasterPty, PtySize};
+use tap::Pipe as _;
+use terminal_size::{Height, Width};
+
+use crate::{
+    helix_log,
+    helix_version::Helix,
+    parse_example::Example,
+    parse_helix_keys::{KeyCode, KeyEvent, KeyModifiers},
+    terminal::{Size, Terminal},
+};
+
+/// Text written by Helix to the terminal
+#[derive(Clone, Debug)]
+pub struct Output {
+    /// When it was written, relative to the start of the session.
+    ///
+    /// Time during which the session was hidden is not included
+    pub time: Duration,
+    /// What was written, including the escape sequences
+    pub data: String,
+}
+
+/// Output of the session, shared with the thread which reads it
+struct Shared {
+    /// When the session started
+    start: Instant,
+    /// When the last output was read
+    last_output: Option<Instant>,
+    /// Since when the output is not recorded
+    hidden_since: Option<Instant>,
+    /// Total time during which the output was not recorded
+    hidden: Duration,
+    /// Output that is recorded
+    output: Vec<Output>,
+    /// Bytes at the end of the last read which are an incomplete UTF-8 character
+    incomplete: Vec<u8>,
+    /// What the terminal currently shows
+    parser: vt100::Parser,
+}
+
+/// Helix editing the file of an example inside of a pseudo-terminal
+pub struct Session {
+    /// Keeps the pseudo-terminal open
+    _master: Box<dyn MasterPty + Send>,
+    /// Input of the terminal
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+    /// The Helix process
+    child: Box<dyn Child + Send + Sync>,
+    /// Reads the output until Helix exits
+    reader: JoinHandle<()>,
+    /// Output of Helix
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Session {
+    /// Start the Helix of the `terminal` inside of `dir` to edit the file of the `example`.
+    ///
+    /// The output is hidden until [`Session::show`] is called
+    ///
+    /// # Errors
+    ///
+    /// If Helix is not installed, or the pseudo-terminal cannot be created
+    pub fn spawn(example: &Example, dir: &Path, terminal: &Terminal) -> miette::Result<Self> {
+        let Terminal { size, helix, .. } = terminal;
+
+        ensure!(
+            which::which(&helix.program).is_ok(),
+            "ERROR (command `{}` not found): You need to \
+            install Helix in order to generate the demos",
+            helix.program.display()
+        );
+
+        let pair = portable_pty::native_pty_system()
+            .openpty(PtySize {
+                rows: size.rows,
+                cols: size.cols,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(|err| miette!("Failed to open a pseudo-terminal: {err}"))?;
+
+        let mut command = command(example, dir, helix);
+        command.env("TERM", "xterm-256color");
+
+        let child = pair
+            .slave
+            .spawn_command(command)
+            .map_err(|err| miette!("Failed to start Helix: {err}"))?;
+        // Once Helix exits, reading the output fails instead of blocking forever
+        drop(pair.slave);
+
+        let writer = pair
+            .master
+            .take_writer()
+            .map_err(|err| miette!("Failed to write to the pseudo-terminal: {err}"))?
+            .pipe(|writer| Arc::new(Mutex::new(writer)));
+        let mut reader = pair
+            .master
+            .try_clone_reader()
+            .map_err(|err| miette!("Failed to read from the pseudo-terminal: {err}"))?;
+
+        let now = Instant::now();
+        let shared = Arc::new(Mutex::new(Shared {
+            start: now,
+            last_output: None,
+            hidden_since: Some(now),
+            hidden: Duration::ZERO,
+            output: Vec::new(),
+            incomplete: Vec::new(),
+            parser: vt100::Parser::new(size.rows, size.cols, 0),
+        }));
+
+        let reader = thread::spawn({
+            let shared = Arc::clone(&shared);
+            let writer = Arc::clone(&writer);
+            move || {
+                let mut buf = [0; 4096];
+                while let Ok(len) = reader.read(&mut buf) {
+                    if len == 0 {
+                        break;
+                    }
+                    let bytes = &buf[..len];
+                    let mut shared = lock(&shared);
+                    shared.push(bytes);
+
+                    // Answer the queries which Helix waits for when detecting
+                    // what the terminal supports
+                    for response in responses(bytes, shared.parser.screen()) {
+                        let _ = lock(&writer).write_all(&response);
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            _master: pair.master,
+            writer,
+            child,
+            reader,
+            shared,
+        })
+    }
+
+    /// Stop recording the output, for example to run commands that are not a part of the demo
+    pub fn hide(&self) {
+        let mut shared = lock(&self.shared);
+        if shared.hidden_since.is_none() {
+            shared.hidden_since = Some(Instant::now());
+        }
+    }
+
+    /// Record the output again.
+    ///
+    /// Starts by redrawing the whole screen, as it may have changed while hidden
+    pub fn show(&self) {
+        let mut shared = lock(&self.shared);
+        let Some(since) = shared.hidden_since.take() else {
+            return;
+        };
+        shared.hidden += since.elapsed();
+
+        let output = Output {
+            time: shared.start.elapsed().saturating_sub(shared.hidden),
+            data: String::from_utf8_lossy(&shared.parser.screen().state_formatted()).into_owned(),
+        };
+        shared.output.push(output);
+    }
+
+    /// Wait until Helix has not written anything for `quiet`, but at most for `timeout`.
+    ///
+    /// Always waits at least `quiet`, as Helix may not have reacted to the last input yet
+    pub fn wait_idle(&self, quiet: Duration, timeout: Duration) {
+        let start = Instant::now();
+        while start.elapsed() < timeout {
+            if start.elapsed() >= quiet
+                && lock(&self.shared)
+                    .last_output
+                    .is_some_and(|last| last.elapsed() >= quiet)
+            {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Time since the start of the session, without the time during which it was hidden
+    pub fn time(&self) -> Duration {
+        let shared = lock(&self.shared);
+        let hidden = shared.hidden
+            + shared
+                .hidden_since
+                .map_or(Duration::ZERO, |since| since.elapsed());
+        shared.start.elapsed().saturating_sub(hidden)
+    }
+
+    /// What the terminal currently shows
+    pub fn screen(&self) -> vt100::Screen {
+        lock(&self.shared).parser.screen().clone()
+    }
+
+    /// Write `bytes` to the input of the terminal
+    ///
+    /// # Errors
+    ///
+    /// If Helix has exited
+    pub fn write(&self, bytes: &[u8]) -> miette::Result<()> {
+        let mut writer = lock(&self.writer);
+        writer
+            .write_all(bytes)
+            .and_then(|()| writer.flush())
+            .map_err(|err| miette!("Failed to send keys to Helix: {err}"))
+    }
+
+    /// Press the `key`
+    ///
+    /// # Errors
+    ///
+    /// If the key cannot be sent through a terminal, or Helix has exited
+    pub fn press(&self, key: &KeyEvent) -> miette::Result<()> {
+        self.write(&encode(key)?)
+    }
+
+    /// Quit Helix without saving, and return the recorded output
+    ///
+    /// # Errors
+    ///
+    /// If Helix does not exit
+    ///
+    /// # Panics
+    ///
+    /// If the thread reading the output has panicked
+    pub fn quit(mut self) -> miette::Result<Vec<Output>> {
+        self.hide();
+        self.write(b"\x1b")?;
+        thread::sleep(Duration::from_millis(50));
+        self.write(b":quit!\r")?;
+
+        let start = Instant::now();
+        while self
+            .child
+            .try_wait()
+            .map_err(|err| miette!("Failed to wait for Helix: {err}"))?
+            .is_none()
+        {
+            if start.elapsed() > Duration::from_secs(10) {
+                let _ = self.child.kill();
+                return Err(miette!("Helix did not exit after `:quit!`"));
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        self.reader
+            .join()
+            .expect("reading the output does not panic");
+
+        Ok(std::mem::take(&mut lock(&self.shared).output))
+    }
+}
+
+impl Shared {
+    /// Record the `bytes` read from the terminal
+    fn push(&mut self, bytes: &[u8]) {
+        let now = Instant::now();
+        self.last_output = Some(now);
+        self.parser.process(bytes);
+
+        self.incomplete.extend_from_slice(bytes);
+        let valid = match std::str::from_utf8(&self.incomplete) {
+            // The rest of the character will arrive in the next read
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            _ => self.incomplete.len(),
+        };
+        let data = String::from_utf8_lossy(&self.incomplete[..valid]).into_owned();
+        self.incomplete.drain(..valid);
+
+        if self.hidden_since.is_none() && !data.is_empty() {
+            self.output.push(Output {
+                time: now.duration_since(self.start).saturating_sub(self.hidden),
+                data,
+            });
+        }
+    }
+}
+
+/// Responses of the terminal to the queries inside of the `output` of Helix
+fn responses(output: &[u8], screen: &vt100::Screen) -> Vec<Vec<u8>> {
+    let contains = |query: &[u8]| output.windows(query.len()).any(|window| window == query);
+    let mut responses = Vec::new();
+
+    // Cursor position
+    if contains(b"\x1b[6n") {
+        let (row, col) = screen.cursor_position();
+        responses.push(format!("\x1b[{};{}R", row + 1, col + 1).into_bytes());
+    }
+
+    // Primary device attributes, a VT220 without any extensions
+    if contains(b"\x1b[c") || contains(b"\x1b[0c") {
+        responses.push(b"\x1b[?62c".to_vec());
+    }
+
+    responses
+}
+
+/// Let the reader type into the Helix of `helix`, which edits the file of the `example`
+/// inside of `dir`, until it exits.
+///
+/// Helix draws onto the terminal of the reader, which is put into raw mode meanwhile.
+/// Returns what the reader typed, as it was read: a lone escape is `<esc>`, while
+/// an escape followed by a key in the same read is the key pressed with alt
+///
+/// # Errors
+///
+/// If Helix is not installed, or the terminal of the reader cannot be put into raw mode
+pub fn interact(example: &Example, dir: &Path, helix: &Helix) -> miette::Result<Vec<Vec<u8>>> {
+    ensure!(
+        which::which(&helix.program).is_ok(),
+        "ERROR (command `{}` not found): You need to \
+        install Helix in order to practise the examples",
+        helix.program.display()
+    );
+
+    let (cols, rows) = terminal_size::terminal_size().map_or_else(
+        || (Size::default().cols, Size::default().rows),
+        |(Width(cols), Height(rows))| (cols, rows),
+    );
+    let pair = portable_pty::native_pty_system()
+        .openpty(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|err| miette!("Failed to open a pseudo-terminal: {err}"))?;
+
+    let mut child = pair
+        .slave
+        .spawn_command(command(example, dir, helix))
+        .map_err(|err| miette!("Failed to start Helix: {err}"))?;
+    drop(pair.slave);
+
+    let mut writer = pair
+        .master
+        .take_writer()
+        .map_err(|err| miette!("Failed to write to the pseudo-terminal: {err}"))?;
+    let mut reader = pair
+        .master
+        .try_clone_reader()
+        .map_err(|err| miette!("Failed to read from the pseudo-terminal: {err}"))?;
+
+    let raw_mode = RawMode::enable()?;
+
+    let output = thread::spawn(move || {
+        let mut stdout = io::stdout();
+        let mut buf = [0; 4096];
+        while let Ok(len @ 1..) = reader.read(&mut buf) {
+            if stdout
+                .write_all(&buf[..len])
+                .and_then(|()| stdout.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    // The input is waited for a little at a time, so that the thread notices when
+    // Helix has exited instead of taking the next key of the reader
+    let typed = Arc::new(Mutex::new(Vec::new()));
+    let stop = Arc::new(AtomicBool::new(false));
+    let input = thread::spawn({
+        let typed = Arc::clone(&typed);
+        let stop = Arc::clone(&stop);
+        move || {
+            let stdin = io::stdin();
+            let mut buf = [0; 256];
+            while !stop.load(Ordering::Relaxed) {
+                match poll(&mut [PollFd::new(stdin.as_fd(), PollFlags::POLLIN)], 50_u16) {
+                    Ok(0) | Err(Errno::EINTR) => continue,
+                    Ok(_) => {}
+                    Err(_) => break,
+                }
+                // Unlike `Stdin`, reading the descriptor does not keep any bytes
+                // in a buffer which `poll` does not know about
+                let Ok(len @ 1..) = unistd::read(stdin.as_raw_fd(), &mut buf) else {
+                    break;
+                };
+                if writer
+                    .write_all(&buf[..len])
+                    .and_then(|()| writer.flush())
+                    .is_err()
+                {
+                    break;
+                }
+                lock(&typed).push(buf[..len].to_vec());
+            }
+        }
+    });
+
+    let status = child
+        .wait()
+        .map_err(|err| miette!("Failed to wait for Helix: {err}"));
+    stop.store(true, Ordering::Relaxed);
+    let _ = input.join();
+    let _ = output.join();
+    drop(raw_mode);
+    status?;
+
+    Ok(std::mem::take(&mut *lock(&typed)))
+}
+
+/// The terminal of the reader in raw mode, which is restored when dropped
+pub struct RawMode {
+    /// Mode of the terminal before
+    original: Termios,
+}
+
+impl RawMode {
+    /// Send each key to the program as it is pressed, without echoing it
+    ///
+    /// # Errors
+    ///
+    /// If the standard input is not a terminal
+    pub fn enable() -> miette::Result<Self> {
+        let stdin = io::stdin();
+        let original = termios::tcgetattr(&stdin)
+            .map_err(|err| miette!("Failed to read the mode of the terminal: {err}"))?;
+        let mut raw = original.clone();
+        termios::cfmakeraw(&mut raw);
+        termios::tcsetattr(&stdin, SetArg::TCSANOW, &raw)
+            .map_err(|err| miette!("Failed to put the terminal into raw mode: {err}"))?;
+        Ok(Self { original })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = termios::tcsetattr(io::stdin(), SetArg::TCSANOW, &self.original);
+    }
+}
+
+/// Helix editing the file of the `example` inside of `dir`
+fn command(example: &Example, dir: &Path, helix: &Helix) -> CommandBuilder {
+    let mut command = CommandBuilder::new(&helix.program);
+    command.arg("-c");
+    command.arg(dir.join("helix-config.toml"));
+    command.args(helix_log::args(&dir.join(example.log_file())));
+    command.arg(dir.join(format!("{}.{}", example.name, example.language)));
+    command.cwd(dir);
+    command
+}
+
+/// Lock the `mutex`, even if another thread has panicked while holding it
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Bytes that a terminal sends to the program when the `key` is pressed
+///
+/// # Errors
+///
+/// If the key cannot be represented in a terminal
+pub fn encode(key: &KeyEvent) -> miette::Result<Vec<u8>> {
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+    let bytes = match key.code {
+        KeyCode::Char(ch) if ctrl => match ch.to_ascii_lowercase() {
+            ch @ ('a'..='z' | '[' | '\\' | ']' | '^' | '_') => {
+                vec![ch.to_ascii_uppercase() as u8 & 0x1f]
+            }
+            ' ' | '@' => vec![0],
+            _ => {
+                return Err(miette!(
+                    "key `{key}` cannot be sent to a terminal",
+                    key = format!("{key:?}")
+                ));
+            }
+        },
+        KeyCode::Char(ch) => ch.to_string().into_bytes(),
+        KeyCode::Enter => b"\r".to_vec(),
+        KeyCode::Tab => b"\t".to_vec(),
+        KeyCode::Backspace => b"\x7f".to_vec(),
+        KeyCode::Esc => b"\x1b".to_vec(),
+        KeyCode::Up => b"\x1b[A".to_vec(),
+        KeyCode::Down => b"\x1b[B".to_vec(),
+        KeyCode::Right => b"\x1b[C".to_vec(),
+        KeyCode::Left => b"\x1b[D".to_vec(),
+        KeyCode::Home => b"\x1b[H".to_vec(),
+        KeyCode::End => b"\x1b[F".to_vec(),
+        KeyCode::Insert => b"\x1b[2~".to_vec(),
+        KeyCode::Delete => b"\x1b[3~".to_vec(),
+        KeyCode::PageUp => b"\x1b[5~".to_vec(),
+        KeyCode::PageDown => b"\x1b[6~".to_vec(),
+        KeyCode::F(n @ 1..=4) => format!("\x1bO{}", char::from(b'P' + n - 1)).into_bytes(),
+        KeyCode::F(n @ 5..=12) => {
+            let code = [15, 17, 18, 19, 20, 21, 23, 24][usize::from(n - 5)];
+            format!("\x1b[{code}~").into_bytes()
+        }
+        _ => {
+            return Err(miette!(
+                "key `{key}` cannot be sent to a terminal",
+                key = format!("{key:?}")
+            ));
+        }
+    };
+
+    // Alt is sent as escape before the key
+    Ok(if alt {
+        [b"\x1b".as_slice(), &bytes].concat()
+    } else {
+        bytes
+    })
+}
+
+/// Keys that the terminal sent when they were pressed, the inverse of [`encode`].
+///
+/// Each element of `input` is one read, see [`interact`]. Also understands the keys
+/// of the kitty keyboard protocol, which Helix enables when the terminal supports it.
+/// Bytes which are not a known key are skipped
+pub fn decode(input: &[Vec<u8>]) -> Vec<KeyEvent> {
+    let mut keys = Vec::new();
+
+    for read in input {
+        let text = String::from_utf8_lossy(read);
+        let mut chars = text.chars().peekable();
+        while let Some(ch) = chars.next() {
+            let key = match ch {
+                '\x1b' => match chars.peek().copied() {
+                    None => Some(key(KeyCode::Esc, KeyModifiers::NONE)),
+                    Some('[' | 'O') => {
+                        let kind = chars.next();
+                        let mut sequence = String::new();
+                        // Parameters, until the final byte
+                        for ch in chars.by_ref() {
+                            sequence.push(ch);
+                            if ('@'..='~').contains(&ch) {
+                                break;
+                            }
+                        }
+                        escape_sequence(kind == Some('O'), &sequence)
+                    }
+                    // A reply of the terminal to a query of Helix, such as its colors,
+                    // which ends with `BEL` or `ESC \`. Otherwise, it is a key with alt
+                    Some(']' | 'P' | '_') if has_string_end(&chars.clone().collect::<String>()) => {
+                        while let Some(ch) = chars.next() {
+                            if ch == '\x07' || (ch == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                                break;
+                            }
+                        }
+                        None
+                    }
+                    Some(_) => chars.next().and_then(|ch| {
+                        plain_key(ch).map(|mut key| {
+                            key.modifiers |= KeyModifiers::ALT;
+                            key
+                        })
+                    }),
+                },
+                ch => plain_key(ch),
+            };
+            keys.extend(key);
+        }
+    }
+
+    keys
+}
+
+/// Whether the `rest` of the input has the end of a string that the terminal sends,
+/// `BEL` or `ESC \`
+fn has_string_end(rest: &str) -> bool {
+    rest.contains('\x07') || rest.contains("\x1b\\")
+}
+
+/// Key of a single character that the terminal sends, such as `\r` for `<enter>`
+fn plain_key(ch: char) -> Option<KeyEvent> {
+    Some(match ch {
+        '\r' | '\n' => key(KeyCode::Enter, KeyModifiers::NONE),
+        '\t' => key(KeyCode::Tab, KeyModifiers::NONE),
+        '\x1b' => key(KeyCode::Esc, KeyModifiers::NONE),
+        '\x7f' | '\x08' => key(KeyCode::Backspace, KeyModifiers::NONE),
+        '\0' => key(KeyCode::Char(' '), KeyModifiers::CONTROL),
+        '\x01'..='\x1a' => key(
+            KeyCode::Char(char::from(ch as u8 - 1 + b'a')),
+            KeyModifiers::CONTROL,
+        ),
+        ch if ch.is_control() => return None,
+        ch => key(KeyCode::Char(ch), KeyModifiers::NONE),
+    })
+}
+
+/// Key of the escape `sequence` after `\x1b[`, or after `\x1bO` if `ss3`
+fn escape_sequence(ss3: bool, sequence: &str) -> Option<KeyEvent> {
+    // Replies of the terminal to queries of Helix, such as `?62;c`, are not keys
+    if sequence.starts_with(['?', '<', '=', '>']) {
+        return None;
+    }
+    let (params, last) = sequence.split_at(sequence.len().checked_sub(1)?);
+    // With shift, the kitty keyboard protocol may send the shifted character
+    // after the code of the key, such as `97:65` for `A`
+    let shifted = params
+        .split(';')
+        .next()
+        .and_then(|param| param.split(':').nth(1))
+        .and_then(|code| code.parse::<u32>().ok());
+    let mut params = params.split(';').map(|param| {
+        param
+            .split(':')
+            .next()
+            .unwrap_or_default()
+            .parse::<u32>()
+            .ok()
+    });
+    let first = params.next().flatten();
+    // The modifiers are sent plus one, as bits of shift, alt and ctrl
+    let modifiers = params.next().flatten().map_or(KeyModifiers::NONE, |mods| {
+        let bits = mods.saturating_sub(1);
+        let mut modifiers = KeyModifiers::NONE;
+        for (bit, modifier) in [
+            (1, KeyModifiers::SHIFT),
+            (2, KeyModifiers::ALT),
+            (4, KeyModifiers::CONTROL),
+        ] {
+            if bits & bit != 0 {
+                modifiers |= modifier;
+            }
+        }
+        modifiers
+    });
+
+    let code = match (last, first) {
+        ("A", _) => KeyCode::Up,
+        ("B", _) => KeyCode::Down,
+        ("C", _) => KeyCode::Right,
+        ("D", _) => KeyCode::Left,
+        // The position of the cursor, which Helix asks for, looks like F3 with modifiers
+        ("R", _) if !ss3 => return None,
+        ("H", _) | ("~", Some(1 | 7)) => KeyCode::Home,
+        ("F", _) | ("~", Some(4 | 8)) => KeyCode::End,
+        ("P" | "Q" | "R" | "S", _) if ss3 || first == Some(1) => {
+            KeyCode::F(last.as_bytes()[0] - b'P' + 1)
+        }
+        ("~", Some(2)) => KeyCode::Insert,
+        ("~", Some(3)) => KeyCode::Delete,
+        ("~", Some(5)) => KeyCode::PageUp,
+        ("~", Some(6)) => KeyCode::PageDown,
+        ("~", Some(code)) => {
+            let n = [15, 17, 18, 19, 20, 21, 23, 24]
+                .iter()
+                .position(|&n| n == code)?;
+            KeyCode::F(u8::try_from(n).ok()? + 5)
+        }
+        // The kitty keyboard protocol sends the code point of the key
+        ("u", Some(code)) => {
+            let shift = modifiers.contains(KeyModifiers::SHIFT);
+            let code = shifted.filter(|_| shift).unwrap_or(code);
+            let mut key = char::from_u32(code).and_then(plain_key)?;
+            key.modifiers |= modifiers;
+            // Shift becomes a part of the character, such as `A` for `a`
+            if let KeyCode::Char(ch) = key.code
+                && shift
+            {
+                let mut upper = ch.to_uppercase();
+                if let (Some(upper), None) = (upper.next(), upper.next()) {
+                    key.code = KeyCode::Char(upper);
+                }
+                key.modifiers -= KeyModifiers::SHIFT;
+            }
+            return Some(key);
+        }
+        _ => return None,
+    };
+
+    Some(key(code, modifiers))
+}
+
+/// The key with the `code` and `modifiers`
+const fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+    KeyEvent { code, modifiers }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_helix_keys::parse_keys;
+
+    fn keys(notation: &str) -> Vec<KeyEvent> {
+        parse_keys(notation, "test").unwrap()
+    }
+
+    #[test]
+    fn encoded_keys_decode_to_themselves() {
+        for key in keys(
+            "aA%<lt><space>é<enter><tab><backspace><esc>\
+            <left><right><up><down><home><end><pageup><pagedown><ins><del>\
+            <F1><F4><F5><F12><ctrl-w><ctrl-space><alt-s><alt-S><alt-ctrl-w>\
+            <alt-enter><alt-esc><alt-minus>",
+        ) {
+            assert_eq!(
+                decode(&[encode(&key).unwrap()]),
+                [key],
+                "{}",
+                key.notation()
+            );
+        }
+    }
+
+    #[test]
+    fn keys_that_a_terminal_sends_the_same_way() {
+        // A terminal cannot tell these keys apart from other keys
+        for (notation, decoded) in [
+            ("<ctrl-m>", "<enter>"),
+            ("<ctrl-i>", "<tab>"),
+            ("<ctrl-h>", "<backspace>"),
+            ("<ctrl-[>", "<esc>"),
+        ] {
+            let [key] = keys(notation).try_into().unwrap();
+            assert_eq!(decode(&[encode(&key).unwrap()]), keys(decoded));
+        }
+        assert!(encode(&keys("<ctrl-1>")[0]).is_err());
+    }
+
+    #[test]
+    fn several_keys_in_one_read() {
+        let encode_all = |keys: &[KeyEvent]| {
+            keys.iter()
+                .map(|key| encode(key).unwrap())
+                .collect::<Vec<_>>()
+                .concat()
+        };
+        let typed = keys("ihi<alt-s><down><ctrl-w><esc>");
+        assert_eq!(decode(&[encode_all(&typed)]), typed);
+
+        // An escape before another key in the same read is alt, so only
+        // separate reads tell `<esc>` and then `s` apart from `<alt-s>`
+        let typed = keys("<esc>s");
+        assert_eq!(decode(&[encode_all(&typed)]), keys("<alt-s>"));
+        assert_eq!(
+            decode(
+                &typed
+                    .iter()
+                    .map(|key| encode(key).unwrap())
+                    .collect::<Vec<_>>()
+            ),
+            typed
+        );
+    }
+
+    #[test]
+    fn kitty_keys() {
+        for (sequence, notation) in [
+            ("\x1b[97u", "a"),
+            ("\x1b[97;3u", "<alt-a>"),
+            ("\x1b[97;5u", "<ctrl-a>"),
+            ("\x1b[13u", "<enter>"),
+            ("\x1b[27u", "<esc>"),
+            ("\x1b[1;5A", "<ctrl-up>"),
+            ("\x1b[3;3~", "<alt-del>"),
+            // Shift is folded into the character
+            ("\x1b[97;2u", "A"),
+            ("\x1b[97;6u", "<ctrl-A>"),
+            ("\x1b[97:65;2u", "A"),
+            ("\x1b[49:33;2u", "!"),
+            // But stays on keys which are not characters
+            ("\x1b[1;2D", "<shift-left>"),
+        ] {
+            assert_eq!(
+                decode(&[sequence.as_bytes().to_vec()]),
+                keys(notation),
+                "{sequence:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn replies_of_the_terminal_are_not_keys() {
+        for reply in [
+            "\x1b[?62;c",
+            "\x1b[?1u",
+            "\x1b[12;40R",
+            "\x1b]11;rgb:0000/0000/0000\x07",
+            "\x1b]10;rgb:ffff/ffff/ffff\x1b\\",
+            "\x1bP1$r0m\x1b\\",
+        ] {
+            assert_eq!(decode(&[reply.as_bytes().to_vec()]), [], "{reply:?}");
+        }
+        // Without the end of a string, it is a key with alt
+        assert_eq!(decode(&[b"\x1b]".to_vec()]), keys("<alt-]>"));
+    }
+}
@@ -0,0 +1,496 @@
+//! Record the demo of an example, and test that it produces `## After`
+//!
+//! There are several recorders:
+//!
+//! - [`Vhs`] records an `.mp4` video
+//! - [`Asciicast`] records an [asciicast](https://docs.asciinema.org/manual/asciicast/v2/)
+//!   which can be played in the browser
+//! - [`Svg`] records an animated `.svg`, which works everywhere an image does
+//! - [`VerifyOnly`] only tests the example, without recording anything
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use miette::{IntoDiagnostic as _, ensure, miette};
+use pretty_assertions::StrComparison;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Example, Paths, generate_helix_config, helix_log, pty,
+    snapshot::{self, Accept},
+    svg,
+    terminal::Terminal,
+    theme::Appearance,
+};
+
+/// Delay between each key in the demos
+pub const TYPING_SPEED: Duration = Duration::from_millis(150);
+
+/// File format of a demo
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    /// `.mp4` video
+    Mp4,
+    /// `.cast` terminal recording
+    Cast,
+    /// Animated `.svg` image
+    Svg,
+}
+
+impl Format {
+    /// Extension of the file
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Mp4 => "mp4",
+            Self::Cast => "cast",
+            Self::Svg => "svg",
+        }
+    }
+}
+
+impl Example {
+    /// Name of the demo in the `format`, recorded with a theme of the `appearance`.
+    ///
+    /// Relative to the generated directory
+    pub fn demo_file(&self, format: Format, appearance: Appearance) -> String {
+        format!(
+            "{}{}.{}",
+            self.name,
+            appearance.suffix(),
+            format.extension()
+        )
+    }
+}
+
+/// Records a demo of an example, while Helix edits the file of the example
+pub trait Recorder: Sync {
+    /// Name of the recorder, which the saved final screen is keyed by
+    fn name(&self) -> &'static str;
+
+    /// Formats of the demo that [`Recorder::record`] places in the directory,
+    /// named by [`Example::demo_file`]
+    fn formats(&self) -> &'static [Format];
+
+    /// Record `example` inside of `dir`, in the `terminal`.
+    ///
+    /// When called, `dir` contains the Helix config and the file of the example
+    /// with the contents of `## Before`. Once finished, the file must contain the result
+    /// of typing the command into Helix, and the snapshot directory must contain
+    /// the text of the final screen named [`Example::screen_file`]
+    ///
+    /// # Errors
+    ///
+    /// If the example could not be recorded
+    fn record(&self, example: &Example, dir: &Path, terminal: &Terminal) -> miette::Result<()>;
+}
+
+/// Records an `.mp4` video with [`vhs`](https://github.com/charmbracelet/vhs),
+/// its captions and the timeline of keys
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Vhs {
+    /// Do not print the progress of `vhs`
+    pub quiet: bool,
+}
+
+impl Recorder for Vhs {
+    fn name(&self) -> &'static str {
+        "vhs"
+    }
+
+    fn formats(&self) -> &'static [Format] {
+        &[Format::Mp4]
+    }
+
+    fn record(&self, example: &Example, dir: &Path, terminal: &Terminal) -> miette::Result<()> {
+        let name = &example.name;
+
+        let tape_file = dir.join(format!("{name}.tape"));
+
+        // Create .tape file
+        //
+        // These are the commands inputted into `vhs`
+        fs::write(&tape_file, example.tape(dir, terminal).to_string()).map_err(|err| {
+            miette!(
+                "Failed to create `{}` for example `{name}`: {err}",
+                tape_file.display()
+            )
+        })?;
+
+        ensure!(
+            which::which("vhs").is_ok(),
+            "ERROR (command `vhs` not found): You need to \
+            install `vhs` in order to generate the demos"
+        );
+
+        // Each key takes `TYPING_SPEED` in the video, and the hidden commands are cut out of it
+        let key_time = |i: usize| TYPING_SPEED * u32::try_from(i).unwrap_or(u32::MAX);
+        let starts = example
+            .steps
+            .iter()
+            .map(|step| key_time(step.key_events.start))
+            .collect::<Vec<_>>();
+        // After the command, `.tape` presses 2 keys and waits for 2 seconds
+        let end = key_time(example.key_events.len() + 2) + Duration::from_secs(2);
+
+        let captions_file = dir.join(example.captions_file());
+        fs::write(&captions_file, example.captions(&starts, end)).map_err(|err| {
+            miette!(
+                "Failed to write `{}` for example `{name}`: {err}",
+                captions_file.display()
+            )
+        })?;
+        example.write_timeline(
+            &(0..example.key_events.len())
+                .map(key_time)
+                .collect::<Vec<_>>(),
+            dir,
+        )?;
+
+        // Generate the .mp4 file preview
+        Command::new("vhs")
+            .arg(tape_file)
+            .stdout(if self.quiet {
+                Stdio::null()
+            } else {
+                Stdio::inherit()
+            })
+            .spawn()
+            .into_diagnostic()?
+            .wait()
+            .into_diagnostic()?;
+
+        // `vhs` does not tell when a message appeared, so play the example again
+        // to find the key after which it is shown
+        if let Some(message) = helix_log::messages(&helix_log::read(example, dir, 0)).first() {
+            fs::write(
+                dir.join(format!("{name}.{}", example.language)),
+                &example.before,
+            )
+            .into_diagnostic()?;
+            play(example, dir, terminal, TYPING_SPEED)?;
+            return Err(helix_log::error(example, message, None));
+        }
+
+        // `vhs` writes each frame of the video as text, the last one is the final screen
+        let frames_file = snapshot_dir(example, dir).join(SCREEN_FRAMES_FILE);
+        let frames = fs::read_to_string(&frames_file).map_err(|err| {
+            miette!(
+                "Failed to read `{}` for example `{name}`: {err}",
+                frames_file.display()
+            )
+        })?;
+        write_screen(example, dir, &last_frame(&frames))
+    }
+}
+
+/// Name of the file in the snapshot directory where `vhs` writes each frame as text
+pub const SCREEN_FRAMES_FILE: &str = "screen.ascii";
+
+/// The last frame of the text output of `vhs`, in which frames are separated
+/// by lines of `─`
+fn last_frame(frames: &str) -> String {
+    frames
+        .lines()
+        .collect::<Vec<_>>()
+        .split(|line| !line.is_empty() && line.chars().all(|ch| ch == '─'))
+        .rfind(|frame| frame.iter().any(|line| !line.trim().is_empty()))
+        .map(|frame| frame.join("\n"))
+        .unwrap_or_default()
+}
+
+/// Records an [asciicast](https://docs.asciinema.org/manual/asciicast/v2/) by running
+/// Helix in a pseudo-terminal.
+///
+/// Keys are typed with the same [`TYPING_SPEED`] as in the `.tape` file,
+/// and the hidden commands which capture the snapshots are cut out of the recording.
+/// Also writes when each key was pressed
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Asciicast;
+
+impl Recorder for Asciicast {
+    fn name(&self) -> &'static str {
+        "asciicast"
+    }
+
+    fn formats(&self) -> &'static [Format] {
+        &[Format::Cast]
+    }
+
+    fn record(&self, example: &Example, dir: &Path, terminal: &Terminal) -> miette::Result<()> {
+        let name = &example.name;
+        let Terminal { theme, size, .. } = terminal;
+        let Played { output, key_times } = play(example, dir, terminal, TYPING_SPEED)?;
+        example.write_timeline(&key_times, dir)?;
+
+        let header = serde_json::json!({
+            "version": 2,
+            "width": size.cols,
+            "height": size.rows,
+            "timestamp": SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|time| time.as_secs())
+                .unwrap_or_default(),
+            "title": example.title,
+            "env": { "TERM": "xterm-256color" },
+            "theme": {
+                "fg": theme.foreground,
+                "bg": theme.background,
+                "palette": theme.palette.join(":"),
+            },
+        });
+
+        let cast = output
+            .iter()
+            .fold(format!("{header}\n"), |mut cast, output| {
+                cast.push_str(
+                    &serde_json::json!([output.time.as_secs_f64(), "o", output.data]).to_string(),
+                );
+                cast.push('\n');
+                cast
+            });
+
+        let cast_file = dir.join(example.demo_file(Format::Cast, theme.appearance));
+        fs::write(&cast_file, cast).map_err(|err| {
+            miette!(
+                "Failed to write `{}` for example `{name}`: {err}",
+                cast_file.display()
+            )
+        })
+    }
+}
+
+/// Records an animated SVG by running Helix in a pseudo-terminal.
+///
+/// Does not need anything besides Helix, such as a browser or `ffmpeg`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Svg;
+
+impl Recorder for Svg {
+    fn name(&self) -> &'static str {
+        "svg"
+    }
+
+    fn formats(&self) -> &'static [Format] {
+        &[Format::Svg]
+    }
+
+    fn record(&self, example: &Example, dir: &Path, terminal: &Terminal) -> miette::Result<()> {
+        let name = &example.name;
+        let Terminal { theme, size, .. } = terminal;
+        let Played { output, .. } = play(example, dir, terminal, TYPING_SPEED)?;
+
+        let svg_file = dir.join(example.demo_file(Format::Svg, theme.appearance));
+        fs::write(&svg_file, svg::render(&output, size.rows, size.cols, theme)).map_err(|err| {
+            miette!(
+                "Failed to write `{}` for example `{name}`: {err}",
+                svg_file.display()
+            )
+        })
+    }
+}
+
+/// Only tests the example, without recording a demo.
+///
+/// Keys are typed as quickly as Helix can handle them
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VerifyOnly;
+
+impl Recorder for VerifyOnly {
+    fn name(&self) -> &'static str {
+        "verify"
+    }
+
+    fn formats(&self) -> &'static [Format] {
+        &[]
+    }
+
+    fn record(&self, example: &Example, dir: &Path, terminal: &Terminal) -> miette::Result<()> {
+        play(example, dir, terminal, Duration::from_millis(20)).map(drop)
+    }
+}
+
+/// What happened while the keys of an example were typed into Helix
+struct Played {
+    /// Output of Helix, without the hidden commands
+    output: Vec<pty::Output>,
+    /// When each key of the command was pressed
+    key_times: Vec<Duration>,
+}
+
+/// Type the keys of the `example` into Helix running inside of `dir` in the `terminal`,
+/// waiting `typing_speed` after each key, and capture the snapshots
+/// and the final screen like the `.tape` file does.
+fn play(
+    example: &Example,
+    dir: &Path,
+    terminal: &Terminal,
+    typing_speed: Duration,
+) -> miette::Result<Played> {
+    let name = &example.name;
+    let ext = &example.language;
+
+    let session = pty::Session::spawn(example, dir, terminal)?;
+
+    // Wait until Helix has drawn the file
+    session.wait_idle(Duration::from_millis(500), Duration::from_secs(10));
+    session.show();
+
+    let snapshot_steps = example.snapshot_steps();
+    let mut key_times = Vec::with_capacity(example.key_events.len());
+    // Messages of the statusline which are already in the log
+    let mut log_offset = helix_log::read(example, dir, 0).len();
+
+    for (i, step) in example.steps.iter().enumerate() {
+        for index in step.key_events.clone() {
+            let key = &example.key_events[index];
+            key_times.push(session.time());
+            session.press(key)?;
+            thread::sleep(typing_speed);
+
+            let log = helix_log::read(example, dir, log_offset);
+            log_offset += log.len();
+            if let Some(message) = helix_log::messages(&log).first() {
+                drop(session.quit());
+                return Err(helix_log::error(example, message, Some(index)));
+            }
+        }
+
+        let step = i + 1;
+        if snapshot_steps.contains(&step) {
+            // Capture the buffer after this step, without it being visible in the demo
+            session.hide();
+            session.wait_idle(Duration::from_millis(50), Duration::from_secs(1));
+            for command in [
+                "write!".to_string(),
+                format!(
+                    "run-shell-command cp {name}.{ext} snapshots/{name}/{snapshot}",
+                    snapshot = example.snapshot_file(step)
+                ),
+            ] {
+                session.write(generate_helix_config::COMMAND_MODE_BYTES)?;
+                thread::sleep(typing_speed);
+                session.write(format!("{command}\r").as_bytes())?;
+                session.wait_idle(Duration::from_millis(50), Duration::from_secs(5));
+            }
+            // Their messages in the statusline are not part of the demo either
+            session.write(generate_helix_config::CLEAR_STATUS_BYTES)?;
+            session.wait_idle(Duration::from_millis(50), Duration::from_secs(1));
+            // Messages of the hidden commands are not part of the example
+            log_offset += helix_log::read(example, dir, log_offset).len();
+            session.show();
+        }
+    }
+
+    session.write(b"\x1b")?;
+    thread::sleep(typing_speed);
+    session.write(b",")?;
+    thread::sleep(typing_speed);
+
+    session.hide();
+    session.write(b":write!\r")?;
+    session.wait_idle(Duration::from_millis(100), Duration::from_secs(5));
+    write_screen(example, dir, &session.screen().contents())?;
+
+    Ok(Played {
+        output: session.quit()?,
+        key_times,
+    })
+}
+
+/// Directory inside of `dir` where the snapshots of the `example` are captured
+fn snapshot_dir(example: &Example, dir: &Path) -> PathBuf {
+    dir.join("snapshots").join(&example.name)
+}
+
+/// Save the text of the final `screen` of the `example` into its snapshot directory
+fn write_screen(example: &Example, dir: &Path, screen: &str) -> miette::Result<()> {
+    let screen_file = snapshot_dir(example, dir).join(Example::screen_file());
+    fs::write(&screen_file, snapshot::normalize_screen(screen)).map_err(|err| {
+        miette!(
+            "Failed to write `{}` for example `{}`: {err}",
+            screen_file.display(),
+            example.name
+        )
+    })
+}
+
+/// Record the demo of `example` with the `recorder` in the `terminal`,
+/// and test that it is correct.
+///
+/// Snapshots and the final screen are saved instead as chosen by `accept`.
+///
+/// The Helix config must already be generated, see [`crate::generate_helix_config`]
+///
+/// # Errors
+///
+/// If the example could not be recorded, it does not produce `## After`,
+/// or a snapshot or the final screen differs from the previous run
+///
+/// # Panics
+///
+/// If the file of the example is removed while it is being recorded
+pub fn verify(
+    recorder: &dyn Recorder,
+    example: &Example,
+    paths: &Paths,
+    terminal: &Terminal,
+    accept: Accept,
+) -> miette::Result<()> {
+    let name = &example.name;
+    let ext = &example.language;
+
+    // The recording may happen in a different directory,
+    // so it needs the full path
+    let dir = paths
+        .generated
+        .canonicalize()
+        .map_err(|err| miette!("Failed to find the generated directory: {err}"))?;
+
+    let modification_file = dir.join(format!("{name}.{ext}"));
+
+    // First, this file has contents Before
+    //
+    // as we modify it, it'll have the contents that we expect from After
+    fs::write(&modification_file, &example.before)
+        .map_err(|err| miette!("Failed to create `Before` for example `{name}.{ext}`: {err}",))?;
+
+    // Helix appends to its log, which must only contain this recording
+    let log_file = dir.join(example.log_file());
+    fs::write(&log_file, "")
+        .map_err(|err| miette!("Failed to create `{}`: {err}", log_file.display()))?;
+
+    // The recording captures the state after each step in here
+    fs::create_dir_all(example.generated_snapshot_dir(paths)).map_err(|err| {
+        miette!("Failed to create snapshot directory for example `{name}`: {err}")
+    })?;
+
+    recorder.record(example, &dir, terminal)?;
+
+    let result = fs::read_to_string(modification_file).expect(
+        "read to not fail, because file exists as \
+        we have just written to it earlier",
+    );
+
+    // Assert that the `## Before` code block is equal to the `## After` code block
+    // once we have executed the commands in `## Commands` code block.
+    ensure!(
+        result.trim() == example.after.trim(),
+        "example `{name}` does not produce `## After`\n\n{}",
+        StrComparison::new(result.trim(), example.after.trim())
+    );
+
+    // The intermediate states and what the viewer sees at the end
+    // must be the same each time we run the example
+    snapshot::check(
+        example,
+        paths,
+        &Example::saved_screen_file(recorder.name(), terminal.size),
+        accept,
+    )
+}
@@ -0,0 +1,375 @@
+//! Machine-readable results of validating and rendering the examples
+//!
+//! Each run writes the following files into the generated directory:
+//!
+//! - `report.json`: status, duration, score and errors of each example
+//! - `junit.xml`: per-example test cases for test dashboards
+//! - `report.sarif`: errors with their location in the markdown file
+//!
+//! When running in GitHub Actions, errors are also printed as annotations
+//! so they appear inline on the markdown files of a pull request.
+
+use std::{
+    env,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use miette::miette;
+use serde::Serialize;
+
+use crate::{helix_version::Version, score::Score};
+
+/// Outcome of a single example
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    /// Example is correct
+    Passed,
+    /// At least one error occured
+    Failed,
+}
+
+/// An error of a single example
+#[derive(Serialize, Debug)]
+pub struct Problem {
+    /// What went wrong
+    pub message: String,
+    /// Path to the markdown file of the example, relative to the current directory
+    pub file: String,
+    /// Line in `file` where the error occured, starting at 1
+    pub line: Option<usize>,
+    /// Column in `file` where the error occured, starting at 1
+    pub column: Option<usize>,
+}
+
+impl Problem {
+    /// Describe the `error` of example `name` in the `root` directory,
+    /// and find where it points to in the markdown file
+    pub fn new(root: &Path, name: &str, error: &miette::Report) -> Self {
+        let file_name = format!("{name}.md");
+
+        let label = error.labels().and_then(|mut labels| labels.next());
+
+        let message = strip_ansi(
+            &label
+                .as_ref()
+                .and_then(|label| label.label())
+                .map_or_else(|| error.to_string(), |reason| format!("{error}: {reason}")),
+        );
+
+        // Only errors in the markdown file have a position that we can report.
+        // For example, errors of parsing the keys point into the command
+        let position = label.and_then(|label| {
+            let contents = error.source_code()?.read_span(label.inner(), 0, 0).ok()?;
+            (contents.name() == Some(file_name.as_str()))
+                .then(|| (contents.line() + 1, contents.column() + 1))
+        });
+
+        let path = root.join(&file_name);
+
+        Self {
+            message,
+            file: path
+                .canonicalize()
+                .ok()
+                .and_then(|path| {
+                    env::current_dir()
+                        .ok()
+                        .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_path_buf))
+                })
+                .unwrap_or(path)
+                .display()
+                .to_string(),
+            line: position.map(|(line, _)| line),
+            column: position.map(|(_, column)| column),
+        }
+    }
+}
+
+/// Result of a single example
+#[derive(Serialize, Debug)]
+pub struct ExampleResult {
+    /// Name of the example, which is its file name without the `.md` extension
+    pub name: String,
+    /// Whether the example succeeded
+    pub status: Status,
+    /// How long it took to process this example
+    #[serde(rename = "duration_ms", serialize_with = "as_millis")]
+    pub duration: Duration,
+    /// Amount of keys in the command. `None` if the command could not be parsed
+    pub keystrokes: Option<usize>,
+    /// Score of the command. `None` if the command could not be parsed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<Score>,
+    /// Everything that went wrong
+    pub errors: Vec<Problem>,
+    /// Versions of Helix which the example works with
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub helix_versions: Vec<Version>,
+    /// Directory with the files to reproduce the example, if it has failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bundle: Option<PathBuf>,
+}
+
+impl ExampleResult {
+    /// Create the result of example `name` in the `root` directory, whose command
+    /// has the `score`, and which either succeeded or failed with an `error`
+    pub fn new(
+        root: &Path,
+        name: &str,
+        duration: Duration,
+        score: Option<Score>,
+        error: Option<&miette::Report>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            status: if error.is_some() {
+                Status::Failed
+            } else {
+                Status::Passed
+            },
+            duration,
+            keystrokes: score.map(|score| score.keystrokes),
+            score,
+            errors: error
+                .map(|error| Problem::new(root, name, error))
+                .into_iter()
+                .collect(),
+            helix_versions: Vec::new(),
+            bundle: None,
+        }
+    }
+}
+
+/// Serialize `Duration` as amount of milliseconds
+fn as_millis<S: serde::Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u128(duration.as_millis())
+}
+
+/// Results of every example in a single run
+#[derive(Serialize, Debug)]
+pub struct Report {
+    /// The command which produced this report, e.g. `validate`
+    pub command: &'static str,
+    /// Result of each example
+    pub examples: Vec<ExampleResult>,
+}
+
+/// Some of the examples have failed
+#[derive(thiserror::Error, Debug, miette::Diagnostic)]
+#[error("{} of the examples failed", errors.len())]
+struct Failures {
+    /// Error of each failed example
+    #[related]
+    errors: Vec<Box<dyn miette::Diagnostic + Send + Sync>>,
+}
+
+impl Report {
+    /// Write the report into `dir`, then fail if there are any `errors`
+    ///
+    /// # Errors
+    ///
+    /// If the report cannot be written, or there are any `errors`
+    pub fn finish(self, dir: &Path, errors: Vec<miette::Report>) -> miette::Result<()> {
+        self.write(dir)?;
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Failures {
+                errors: errors.into_iter().map(Into::into).collect(),
+            }
+            .into())
+        }
+    }
+
+    /// Write the report in every format into `dir`
+    fn write(&self, dir: &Path) -> miette::Result<()> {
+        let write = |file: &str, contents: String| {
+            fs::write(dir.join(file), contents)
+                .map_err(|err| miette!("Failed to write report `{file}`: {err}"))
+        };
+
+        write(
+            "report.json",
+            serde_json::to_string_pretty(self)
+                .map_err(|err| miette!("Failed to serialize report: {err}"))?,
+        )?;
+        write("junit.xml", self.junit())?;
+        write(
+            "report.sarif",
+            serde_json::to_string_pretty(&self.sarif())
+                .map_err(|err| miette!("Failed to serialize SARIF report: {err}"))?,
+        )?;
+
+        if env::var("GITHUB_ACTIONS").is_ok_and(|var| var == "true") {
+            print!("{}", self.github_annotations());
+        }
+
+        Ok(())
+    }
+
+    /// [JUnit XML](https://github.com/testmoapp/junitxml) with a test case for each example
+    fn junit(&self) -> String {
+        let failures = self
+            .examples
+            .iter()
+            .filter(|example| example.status == Status::Failed)
+            .count();
+        let time = self
+            .examples
+            .iter()
+            .map(|example| example.duration)
+            .sum::<Duration>()
+            .as_secs_f64();
+
+        let mut xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+            <testsuites name=\"helix-golf\" tests=\"{tests}\" failures=\"{failures}\" time=\"{time:.3}\">\n  \
+            <testsuite name=\"{command}\" tests=\"{tests}\" failures=\"{failures}\" time=\"{time:.3}\">\n",
+            tests = self.examples.len(),
+            command = self.command,
+        );
+
+        for example in &self.examples {
+            let _ = write!(
+                xml,
+                "    <testcase name=\"{name}\" classname=\"{command}\" time=\"{time:.3}\"",
+                name = xml_escape(&example.name),
+                command = self.command,
+                time = example.duration.as_secs_f64(),
+            );
+
+            if example.errors.is_empty() {
+                xml.push_str("/>\n");
+                continue;
+            }
+
+            xml.push_str(">\n");
+            for error in &example.errors {
+                let _ = writeln!(
+                    xml,
+                    "      <failure message=\"{message}\">{file}{position}\n{message}</failure>",
+                    message = xml_escape(&error.message),
+                    file = xml_escape(&error.file),
+                    position = match (error.line, error.column) {
+                        (Some(line), Some(column)) => format!(":{line}:{column}"),
+                        _ => String::new(),
+                    },
+                );
+            }
+            xml.push_str("    </testcase>\n");
+        }
+
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+
+        xml
+    }
+
+    /// [SARIF](https://sarifweb.azurewebsites.net/) log with a result for each error
+    fn sarif(&self) -> serde_json::Value {
+        let results = self
+            .examples
+            .iter()
+            .flat_map(|example| &example.errors)
+            .map(|error| {
+                let mut region = serde_json::Map::new();
+                if let Some(line) = error.line {
+                    region.insert("startLine".to_string(), line.into());
+                }
+                if let Some(column) = error.column {
+                    region.insert("startColumn".to_string(), column.into());
+                }
+
+                serde_json::json!({
+                    "ruleId": self.command,
+                    "level": "error",
+                    "message": { "text": error.message },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": error.file },
+                            "region": region,
+                        }
+                    }]
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "helix-golf",
+                        "informationUri": "https://github.com/nik-rev/helix-golf",
+                        "rules": [{
+                            "id": self.command,
+                            "shortDescription": { "text": format!("Example fails `{}`", self.command) },
+                        }]
+                    }
+                },
+                "results": results,
+            }]
+        })
+    }
+
+    /// [Workflow commands](https://docs.github.com/en/actions/reference/workflow-commands-for-github-actions#setting-an-error-message)
+    /// which show each error inline in a pull request
+    fn github_annotations(&self) -> String {
+        self.examples
+            .iter()
+            .flat_map(|example| &example.errors)
+            .fold(String::new(), |mut out, error| {
+                let mut properties = format!("file={}", escape_property(&error.file));
+                if let Some(line) = error.line {
+                    let _ = write!(properties, ",line={line}");
+                }
+                if let Some(column) = error.column {
+                    let _ = write!(properties, ",col={column}");
+                }
+
+                let _ = writeln!(out, "::error {properties}::{}", escape_data(&error.message));
+                out
+            })
+    }
+}
+
+/// Escape characters which end the message of a workflow command
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape characters which end a property of a workflow command, such as `file=`
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Escape characters which have a special meaning in XML
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Remove the color codes, for example the ones in the diff of `pretty_assertions`
+fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' {
+            // skip until the end of the escape sequence, e.g. `\x1b[31m`
+            chars.by_ref().find(char::is_ascii_alphabetic);
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
@@ -0,0 +1,160 @@
+//! How golfers score the command of an example
+//!
+//! Counting every key treats typed text the same as commands, so the score has
+//! three metrics:
+//!
+//! - keystrokes: every key that is pressed
+//! - commands: each command counts once, with the keys of its sub-menu such as `gg`,
+//!   the character it waits for such as `x` in `fx`, its count, and the text
+//!   of the prompt it opens
+//! - inserted characters: text typed in insert mode, which is counted apart from the commands
+
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helix_mode::{Mode, ModeTracker, Role},
+    parse_example::Example,
+    parse_helix_keys::{KeyCode, KeyEvent, KeyModifiers},
+};
+
+/// Score of the command of an example, lower is better
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Score {
+    /// Every key that is pressed
+    pub keystrokes: usize,
+    /// Commands, with the keys that they wait for collapsed into them
+    pub commands: usize,
+    /// Characters typed in insert mode
+    pub inserted_characters: usize,
+}
+
+/// One of the metrics of a [`Score`]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Metric {
+    /// [`Score::keystrokes`]
+    #[default]
+    Keystrokes,
+    /// [`Score::commands`]
+    Commands,
+    /// [`Score::inserted_characters`]
+    InsertedCharacters,
+}
+
+impl Score {
+    /// Value of the `metric`
+    pub const fn get(self, metric: Metric) -> usize {
+        match metric {
+            Metric::Keystrokes => self.keystrokes,
+            Metric::Commands => self.commands,
+            Metric::InsertedCharacters => self.inserted_characters,
+        }
+    }
+}
+
+impl Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        write!(
+            f,
+            "{} keystroke{}, {} command{}, {} inserted character{}",
+            self.keystrokes,
+            plural(self.keystrokes),
+            self.commands,
+            plural(self.commands),
+            self.inserted_characters,
+            plural(self.inserted_characters),
+        )
+    }
+}
+
+impl Example {
+    /// Score of the command of this example
+    pub fn score(&self) -> Score {
+        score(&self.key_events)
+    }
+}
+
+/// Score of the `keys`, typed starting in normal mode
+pub fn score(keys: &[KeyEvent]) -> Score {
+    let mut tracker = ModeTracker::default();
+    let mut score = Score {
+        keystrokes: keys.len(),
+        ..Score::default()
+    };
+
+    for key in keys {
+        match tracker.role(key) {
+            Role::Binding {
+                mode: Mode::Insert, ..
+            } if is_text(key) => score.inserted_characters += 1,
+            Role::Binding {
+                continues: false, ..
+            } => score.commands += 1,
+            // Part of the previous command
+            Role::Binding {
+                continues: true, ..
+            }
+            | Role::Argument => {}
+        }
+        tracker.feed(key);
+    }
+
+    score
+}
+
+/// Whether the `key` inserts text in insert mode
+fn is_text(key: &KeyEvent) -> bool {
+    (key.modifiers - KeyModifiers::SHIFT).is_empty()
+        && matches!(key.code, KeyCode::Char(_) | KeyCode::Enter | KeyCode::Tab)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_helix_keys::parse_keys;
+
+    /// Score of the `keys` in the notation of the examples
+    fn score_of(keys: &str) -> Score {
+        score(&parse_keys(keys, "test").unwrap())
+    }
+
+    #[test]
+    fn counts_each_metric() {
+        for (keys, keystrokes, commands, inserted_characters) in [
+            // `ms"` waits for the character, `i,<esc>` inserts one
+            (r#"%<alt-s>ms"<alt-J>i,<esc>xms ms["#, 16, 9, 1),
+            // The count is a part of the motion
+            ("3jd", 3, 2, 0),
+            ("ihello<esc>", 7, 2, 5),
+            // The text of the prompt is a part of the command
+            (":w<enter>", 3, 1, 0),
+            ("ggfx", 4, 2, 0),
+            // Keys with modifiers do not insert text
+            ("ia<ctrl-w><esc>", 4, 3, 1),
+        ] {
+            assert_eq!(
+                score_of(keys),
+                Score {
+                    keystrokes,
+                    commands,
+                    inserted_characters,
+                },
+                "score of `{keys}`"
+            );
+        }
+    }
+
+    #[test]
+    fn displays_plurals() {
+        assert_eq!(
+            score_of("i").to_string(),
+            "1 keystroke, 1 command, 0 inserted characters"
+        );
+        assert_eq!(
+            score_of("iab").to_string(),
+            "3 keystrokes, 1 command, 2 inserted characters"
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Contents of the buffer after each step of the explanation list,
+//! and the text of the final screen of the demo
+
+use std::{fs, path::PathBuf};
+
+use miette::{ensure, miette};
+use pretty_assertions::StrComparison;
+
+use crate::{
+    helix_mode::ModeTracker, parse_example::Example, paths::Paths, terminal::Size,
+    theme::Appearance,
+};
+
+impl Example {
+    /// Numbers of the steps (starting at 1) after which Helix is not
+    /// waiting for more keys, so the state of the buffer can be captured
+    pub fn snapshot_steps(&self) -> Vec<usize> {
+        let mut tracker = ModeTracker::default();
+
+        self.steps
+            .iter()
+            .enumerate()
+            .filter_map(|(i, step)| {
+                for key in &self.key_events[step.key_events.clone()] {
+                    tracker.feed(key);
+                }
+                (tracker.is_settled() && !step.key_events.is_empty()).then_some(i + 1)
+            })
+            .collect()
+    }
+
+    /// Name of the file containing the buffer after the `step`,
+    /// relative to the snapshot directory of this example
+    pub fn snapshot_file(&self, step: usize) -> String {
+        format!("{step}.{}", self.language)
+    }
+
+    /// Name of the screenshot showing selections after the `step`, taken with a theme
+    /// of the `appearance`, relative to the snapshot directory of this example
+    pub fn screenshot_file(step: usize, appearance: Appearance) -> String {
+        format!("{step}{}.png", appearance.suffix())
+    }
+
+    /// Name of the file containing the text of the screen once the command has finished,
+    /// relative to the snapshot directory of this example
+    pub const fn screen_file() -> &'static str {
+        "screen.txt"
+    }
+
+    /// Name of the saved [`Example::screen_file`] of the `recorder`, see [`Recorder::name`],
+    /// in a terminal of the `size`.
+    ///
+    /// Each recorder draws the screen in its own way, and its text depends on the size
+    ///
+    /// [`Recorder::name`]: crate::recorder::Recorder::name
+    pub fn saved_screen_file(recorder: &str, size: Size) -> String {
+        format!("screen.{recorder}.{}x{}.txt", size.cols, size.rows)
+    }
+
+    /// Directory where the snapshots captured by `vhs` are placed
+    pub fn generated_snapshot_dir(&self, paths: &Paths) -> PathBuf {
+        paths.generated.join("snapshots").join(&self.name)
+    }
+
+    /// Directory which holds the snapshots of the previous run
+    pub fn snapshot_dir(&self, paths: &Paths) -> PathBuf {
+        paths.snapshots.join(&self.name)
+    }
+}
+
+/// Which captured snapshots are saved instead of being compared with the saved ones
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Accept {
+    /// Snapshots which have not been saved before, such as of a new example
+    #[default]
+    New,
+    /// Every snapshot, to update the ones which have changed on purpose
+    All,
+    /// No snapshot, so that each one must have been saved before, such as in CI
+    Nothing,
+}
+
+/// The `text` of a screen without trailing whitespace, so that it can be compared
+pub fn normalize_screen(text: &str) -> String {
+    let mut screen = text
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string();
+    screen.push('\n');
+    screen
+}
+
+/// Make sure the snapshots and the final screen that have just been captured
+/// are the same as the ones from the previous run.
+///
+/// The captured snapshots are saved instead as chosen by `accept`.
+/// The final screen is saved as `saved_screen`, see [`Example::saved_screen_file`]
+///
+/// # Errors
+///
+/// If a snapshot was not captured, differs from the previous run, was not saved before
+/// with [`Accept::Nothing`], or cannot be saved
+pub fn check(
+    example: &Example,
+    paths: &Paths,
+    saved_screen: &str,
+    accept: Accept,
+) -> miette::Result<()> {
+    let name = &example.name;
+    let generated_dir = example.generated_snapshot_dir(paths);
+    let dir = example.snapshot_dir(paths);
+
+    let captured = example
+        .snapshot_steps()
+        .into_iter()
+        .map(|step| {
+            let file = example.snapshot_file(step);
+            (format!("Snapshot after step {step}"), file.clone(), file)
+        })
+        .chain([(
+            "Final screen".to_string(),
+            Example::screen_file().to_string(),
+            saved_screen.to_string(),
+        )])
+        .map(|(what, captured, saved)| {
+            fs::read_to_string(generated_dir.join(&captured))
+                .map(|contents| (saved, contents))
+                .map_err(|err| miette!("{what} of example `{name}` was not captured: {err}"))
+        })
+        .collect::<miette::Result<Vec<_>>>()?;
+
+    fs::create_dir_all(&dir)
+        .map_err(|err| miette!("Failed to create `{}`: {err}", dir.display()))?;
+
+    for (file, contents) in &captured {
+        let path = dir.join(file);
+
+        ensure!(
+            accept != Accept::Nothing || path.exists(),
+            "Snapshot `{file}` of example `{name}` has not been saved.\n\
+            Generate the demo with `--accept`, and commit the snapshots in `{}`.",
+            dir.display()
+        );
+
+        // Saved for the first time, or updated on purpose
+        if accept == Accept::All || !path.exists() {
+            fs::write(&path, contents).map_err(|err| {
+                miette!("Failed to save snapshot `{file}` of example `{name}`: {err}")
+            })?;
+            continue;
+        }
+
+        let previous = fs::read_to_string(&path).unwrap_or_default();
+
+        ensure!(
+            previous == *contents,
+            "Snapshot `{file}` of example `{name}` differs from the previous run.\n\
+            If this change is intended, generate the demo again with `--accept`.\n\n{}",
+            StrComparison::new(&previous, contents),
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,279 @@
+//! Render the output of Helix in a pseudo-terminal as a self-contained animated SVG.
+//!
+//! Each distinct screen becomes a frame. All frames are placed next to each other,
+//! and a CSS animation moves them through the visible area.
+
+use std::{fmt::Write as _, time::Duration};
+
+use crate::{pty, theme::Theme};
+
+/// Width of a single cell of the terminal, in pixels
+const CELL_WIDTH: f64 = 8.4;
+
+/// Height of a single cell of the terminal, in pixels
+const CELL_HEIGHT: f64 = 18.0;
+
+/// Size of the font, in pixels
+const FONT_SIZE: f64 = 14.0;
+
+/// How long the last frame is shown before the animation starts again
+const LAST_FRAME: Duration = Duration::from_secs(2);
+
+/// Screens which are shown for a shorter time than this are skipped
+const MIN_FRAME: Duration = Duration::from_millis(16);
+
+/// What the terminal shows at some point in time
+struct Frame {
+    /// When the screen starts to be visible
+    time: Duration,
+    /// The screen
+    screen: vt100::Screen,
+}
+
+/// Appearance of a cell
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Style {
+    /// Color of the text
+    fg: vt100::Color,
+    /// Color of the background
+    bg: vt100::Color,
+    /// Bold text
+    bold: bool,
+    /// Italic text
+    italic: bool,
+    /// Underlined text
+    underline: bool,
+}
+
+impl Style {
+    /// Appearance of the `cell`
+    fn of(cell: &vt100::Cell) -> Self {
+        let (fg, bg) = if cell.inverse() {
+            (cell.bgcolor(), cell.fgcolor())
+        } else {
+            (cell.fgcolor(), cell.bgcolor())
+        };
+
+        Self {
+            fg,
+            bg,
+            bold: cell.bold(),
+            italic: cell.italic(),
+            underline: cell.underline(),
+        }
+    }
+}
+
+/// Render the `output` of a terminal with `rows` and `cols`, in the colors of the `theme`
+pub fn render(output: &[pty::Output], rows: u16, cols: u16, theme: &Theme) -> String {
+    let frames = frames(output, rows, cols);
+
+    let width = f64::from(cols) * CELL_WIDTH;
+    let height = f64::from(rows) * CELL_HEIGHT;
+    let total = frames
+        .last()
+        .map_or(Duration::ZERO, |frame| frame.time)
+        .saturating_add(LAST_FRAME);
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">
+<style>
+text {{ font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, "DejaVu Sans Mono", monospace; font-size: {FONT_SIZE}px; white-space: pre; dominant-baseline: text-before-edge; }}
+.b {{ font-weight: bold; }}
+.i {{ font-style: italic; }}
+.u {{ text-decoration: underline; }}
+.frames {{ animation: play {total:.3}s step-end infinite; }}
+@keyframes play {{
+"#,
+        total = total.as_secs_f64(),
+    );
+
+    // Horizontal position of each frame
+    let offsets = std::iter::successors(Some(0.0), |offset| Some(offset + width));
+
+    for (frame, offset) in frames.iter().zip(offsets.clone()) {
+        let _ = writeln!(
+            svg,
+            "  {:.3}% {{ transform: translateX({:.1}px); }}",
+            frame.time.as_secs_f64() / total.as_secs_f64() * 100.0,
+            -offset
+        );
+    }
+
+    let _ = write!(
+        svg,
+        r#"}}
+</style>
+<rect width="100%" height="100%" fill="{background}"/>
+<g class="frames">
+"#,
+        background = theme.background
+    );
+
+    for (frame, offset) in frames.iter().zip(offsets) {
+        let _ = writeln!(svg, r#"<g transform="translate({offset:.1})">"#);
+        render_screen(&mut svg, &frame.screen, theme);
+        svg.push_str("</g>\n");
+    }
+
+    svg.push_str("</g>\n</svg>\n");
+
+    svg
+}
+
+/// Replay the `output` and collect each distinct screen
+fn frames(output: &[pty::Output], rows: u16, cols: u16) -> Vec<Frame> {
+    let mut parser = vt100::Parser::new(rows, cols, 0);
+    let mut frames: Vec<Frame> = Vec::new();
+
+    for (i, event) in output.iter().enumerate() {
+        parser.process(event.data.as_bytes());
+
+        // Helix draws a single screen in several writes
+        if output
+            .get(i + 1)
+            .is_some_and(|next| next.time.saturating_sub(event.time) < MIN_FRAME)
+        {
+            continue;
+        }
+
+        let screen = parser.screen();
+        if frames
+            .last()
+            .is_some_and(|last| last.screen.contents_formatted() == screen.contents_formatted())
+        {
+            continue;
+        }
+
+        frames.push(Frame {
+            time: event.time,
+            screen: screen.clone(),
+        });
+    }
+
+    // The animation starts with the first frame
+    if let Some(first) = frames.first_mut() {
+        first.time = Duration::ZERO;
+    }
+
+    frames
+}
+
+/// Add the backgrounds and text of the `screen` to the `svg`
+fn render_screen(svg: &mut String, screen: &vt100::Screen, theme: &Theme) {
+    let (rows, cols) = screen.size();
+
+    for row in 0..rows {
+        let y = f64::from(row) * CELL_HEIGHT;
+
+        // Consecutive cells with the same appearance
+        let mut runs: Vec<(u16, u16, Style, String)> = Vec::new();
+        for col in 0..cols {
+            let Some(cell) = screen.cell(row, col) else {
+                continue;
+            };
+            if cell.is_wide_continuation() {
+                continue;
+            }
+
+            let style = Style::of(cell);
+            let width = if cell.is_wide() { 2 } else { 1 };
+            let text = if cell.has_contents() {
+                cell.contents()
+            } else {
+                " "
+            };
+
+            match runs.last_mut() {
+                Some((_, len, last, contents)) if *last == style => {
+                    *len += width;
+                    contents.push_str(text);
+                }
+                _ => runs.push((col, width, style, text.to_string())),
+            }
+        }
+
+        for (col, len, style, text) in runs {
+            let x = f64::from(col) * CELL_WIDTH;
+            let width = f64::from(len) * CELL_WIDTH;
+
+            if style.bg != vt100::Color::Default {
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{x:.1}" y="{y:.1}" width="{width:.1}" height="{CELL_HEIGHT}" fill="{}"/>"#,
+                    color(style.bg, theme.background, theme)
+                );
+            }
+
+            let trimmed = text.trim_end();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            let classes = [
+                (style.bold, "b"),
+                (style.italic, "i"),
+                (style.underline, "u"),
+            ]
+            .into_iter()
+            .filter_map(|(enabled, class)| enabled.then_some(class))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+            let _ = writeln!(
+                svg,
+                r#"<text x="{x:.1}" y="{y:.1}" fill="{fill}"{class}>{text}</text>"#,
+                fill = color(style.fg, theme.foreground, theme),
+                class = if classes.is_empty() {
+                    String::new()
+                } else {
+                    format!(r#" class="{classes}""#)
+                },
+                text = xml_escape(trimmed),
+            );
+        }
+    }
+
+    if !screen.hide_cursor() {
+        let (row, col) = screen.cursor_position();
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="{CELL_WIDTH}" height="{CELL_HEIGHT}" fill="{}" opacity="0.5"/>"#,
+            f64::from(col) * CELL_WIDTH,
+            f64::from(row) * CELL_HEIGHT,
+            theme.foreground,
+        );
+    }
+}
+
+/// The `color` as `#rrggbb`, where the default color is `default`
+fn color(color: vt100::Color, default: &str, theme: &Theme) -> String {
+    match color {
+        vt100::Color::Default => default.to_string(),
+        vt100::Color::Idx(i @ 0..16) => theme.palette[usize::from(i)].to_string(),
+        // 6x6x6 color cube
+        vt100::Color::Idx(i @ 16..232) => {
+            let level = |n: u8| if n == 0 { 0 } else { n * 40 + 55 };
+            let i = i - 16;
+            format!(
+                "#{:02x}{:02x}{:02x}",
+                level(i / 36),
+                level(i / 6 % 6),
+                level(i % 6)
+            )
+        }
+        // Grayscale
+        vt100::Color::Idx(i) => {
+            let level = (i - 232) * 10 + 8;
+            format!("#{level:02x}{level:02x}{level:02x}")
+        }
+        vt100::Color::Rgb(r, g, b) => format!("#{r:02x}{g:02x}{b:02x}"),
+    }
+}
+
+/// Escape characters which have a special meaning in XML
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
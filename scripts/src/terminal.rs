@@ -0,0 +1,181 @@
+//! Size of the terminal in which the demo of an example is recorded
+//!
+//! The terminal is as large as the contents of the example need: the longest line and
+//! the most lines of `## Before` and `## After`, plus the gutter and the statusline.
+//! The font becomes smaller for wide examples, and the height follows the amount of lines,
+//! both inside of [`Bounds`].
+//!
+//! Only the text of the example is measured, so that the size is the same on every run.
+//! The states of the buffer between the steps of the command are not known before the
+//! demo is recorded, so a state with longer or more lines than both `## Before` and
+//! `## After` may wrap or scroll in the demo.
+
+use miette::{Severity, miette};
+
+use crate::{helix_version::Helix, parse_example::Example, theme::Theme};
+
+/// Width of a cell is `font_size * CELL_WIDTH.0 / CELL_WIDTH.1` pixels, rounded up
+const CELL_WIDTH: (u32, u32) = (3, 5);
+
+/// Height of a cell is `font_size * CELL_HEIGHT.0 / CELL_HEIGHT.1` pixels, rounded up
+const CELL_HEIGHT: (u32, u32) = (6, 5);
+
+/// Rows below the buffer: the statusline and the command line
+const STATUSLINE_ROWS: usize = 2;
+
+/// Columns of the statusline besides the file name: mode, selections and position
+const STATUSLINE_COLUMNS: usize = 40;
+
+/// Columns taken by a tab in Helix
+const TAB_WIDTH: usize = 4;
+
+/// Limits of the size of the terminal
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Bounds {
+    /// Width of the demo, in pixels
+    pub width: u32,
+    /// Smallest font size, used when the longest line is very long
+    pub min_font_size: u32,
+    /// Largest font size, used when all lines are short
+    pub max_font_size: u32,
+    /// Smallest height of the demo, in pixels
+    pub min_height: u32,
+    /// Largest height of the demo, in pixels
+    pub max_height: u32,
+}
+
+impl Default for Bounds {
+    fn default() -> Self {
+        Self {
+            width: 1200,
+            min_font_size: 12,
+            max_font_size: 20,
+            min_height: 240,
+            max_height: 900,
+        }
+    }
+}
+
+/// Dimensions of the terminal
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Size {
+    /// Amount of columns
+    pub cols: u16,
+    /// Amount of rows
+    pub rows: u16,
+    /// Size of the font, in pixels
+    pub font_size: u32,
+    /// Width of the demo, in pixels
+    pub width: u32,
+    /// Height of the demo, in pixels
+    pub height: u32,
+}
+
+impl Default for Size {
+    /// What the demos used before the terminal was sized to fit the example
+    fn default() -> Self {
+        Self {
+            cols: 100,
+            rows: 25,
+            font_size: 20,
+            width: 1200,
+            height: 600,
+        }
+    }
+}
+
+impl Size {
+    /// Size inside of the `bounds` with the largest font which shows `## Before` and
+    /// `## After` of the `example` without wrapping or scrolling.
+    ///
+    /// If the example does not fit, the smallest font is used together with a warning
+    pub fn fit(example: &Example, bounds: &Bounds) -> (Self, Option<miette::Report>) {
+        let states = [example.before.as_str(), example.after.as_str()];
+
+        let (lines, longest) = states.into_iter().fold((1, 0), |(lines, longest), state| {
+            (
+                lines.max(state.lines().count()),
+                state
+                    .lines()
+                    .map(|line| {
+                        line.chars()
+                            .map(|ch| if ch == '\t' { TAB_WIDTH } else { 1 })
+                            .sum()
+                    })
+                    .fold(longest, usize::max),
+            )
+        });
+
+        // Line numbers are at least 3 columns wide, followed by the spacer
+        let gutter = lines.to_string().len().max(3) + 1;
+        // The cursor can be placed after the end of the line
+        let cols = (gutter + longest + 1)
+            .max(STATUSLINE_COLUMNS + example.name.len() + example.language.len() + 1);
+        let rows = lines + STATUSLINE_ROWS;
+
+        let size = Self::inside(bounds, cols, rows);
+
+        let warning = (usize::from(size.cols) < cols || usize::from(size.rows) < rows).then(|| {
+            miette!(
+                severity = Severity::Warning,
+                help = "Increase the width or the height of the demos, or decrease the smallest font size",
+                "example `{}` needs {cols} columns and {rows} rows, \
+                but the terminal only has {} columns and {} rows",
+                example.name,
+                size.cols,
+                size.rows,
+            )
+        });
+
+        (size, warning)
+    }
+
+    /// The largest font inside of the `bounds` with at least `cols` and `rows`
+    fn inside(bounds: &Bounds, cols: usize, rows: usize) -> Self {
+        let cols = u32::try_from(cols).unwrap_or(u32::MAX);
+        let rows = u32::try_from(rows).unwrap_or(u32::MAX);
+
+        let fits = |font_size| {
+            let (cell_width, cell_height) = cell(font_size);
+            bounds.width / cell_width >= cols && bounds.max_height / cell_height >= rows
+        };
+        let font_size = (bounds.min_font_size..=bounds.max_font_size)
+            .rev()
+            .find(|&font_size| fits(font_size))
+            .unwrap_or(bounds.min_font_size);
+
+        let (cell_width, cell_height) = cell(font_size);
+        // The largest height wins over the smallest one, if they are the wrong way around
+        let height = rows
+            .saturating_mul(cell_height)
+            .clamp(bounds.min_height.min(bounds.max_height), bounds.max_height);
+
+        Self {
+            cols: u16::try_from(bounds.width / cell_width).unwrap_or(u16::MAX),
+            rows: u16::try_from(height / cell_height).unwrap_or(u16::MAX),
+            font_size,
+            width: bounds.width,
+            height,
+        }
+    }
+}
+
+/// Width and height of a cell with the `font_size`, in pixels
+fn cell(font_size: u32) -> (u32, u32) {
+    let font_size = font_size.max(1);
+    (
+        (font_size * CELL_WIDTH.0).div_ceil(CELL_WIDTH.1),
+        (font_size * CELL_HEIGHT.0).div_ceil(CELL_HEIGHT.1),
+    )
+}
+
+/// Terminal in which a demo is recorded
+#[derive(Clone, Debug)]
+pub struct Terminal {
+    /// Colors of the terminal
+    pub theme: Theme,
+    /// Dimensions of the terminal
+    pub size: Size,
+    /// Helix which runs inside of the terminal
+    pub helix: Helix,
+}
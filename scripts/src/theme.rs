@@ -0,0 +1,68 @@
+//! Colors of the terminal in which the demos are recorded
+
+use serde::{Deserialize, Serialize};
+
+/// Whether a theme has a dark or a light background
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Appearance {
+    /// Light text on a dark background
+    Dark,
+    /// Dark text on a light background
+    Light,
+}
+
+impl Appearance {
+    /// Added to the name of generated files before the extension.
+    ///
+    /// Dark demos have no suffix, as they are the default
+    pub const fn suffix(self) -> &'static str {
+        match self {
+            Self::Dark => "",
+            Self::Light => ".light",
+        }
+    }
+}
+
+/// Colors of a terminal, as `#rrggbb`
+///
+/// Helix uses the `base16_transparent` theme, which only uses the colors
+/// of the terminal. So the theme of the terminal is also the theme of Helix
+#[derive(Clone, Copy, Debug)]
+pub struct Theme {
+    /// Name of the theme, as known to `vhs`
+    pub name: &'static str,
+    /// Whether the background is dark or light
+    pub appearance: Appearance,
+    /// Color of text without a color
+    pub foreground: &'static str,
+    /// Color of the background without a color
+    pub background: &'static str,
+    /// The 16 ANSI colors: black, red, green, yellow, blue, magenta, cyan and white,
+    /// followed by their bright variants
+    pub palette: [&'static str; 16],
+}
+
+/// The default theme of the demos
+pub const CATPPUCCIN_MOCHA: Theme = Theme {
+    name: "Catppuccin Mocha",
+    appearance: Appearance::Dark,
+    foreground: "#cdd6f4",
+    background: "#1e1e2e",
+    palette: [
+        "#45475a", "#f38ba8", "#a6e3a1", "#f9e2af", "#89b4fa", "#f5c2e7", "#94e2d5", "#bac2de",
+        "#585b70", "#f38ba8", "#a6e3a1", "#f9e2af", "#89b4fa", "#f5c2e7", "#94e2d5", "#a6adc8",
+    ],
+};
+
+/// Light counterpart of [`CATPPUCCIN_MOCHA`]
+pub const CATPPUCCIN_LATTE: Theme = Theme {
+    name: "Catppuccin Latte",
+    appearance: Appearance::Light,
+    foreground: "#4c4f69",
+    background: "#eff1f5",
+    palette: [
+        "#5c5f77", "#d20f39", "#40a02b", "#df8e1d", "#1e66f5", "#ea76cb", "#179299", "#acb0be",
+        "#6c6f85", "#d20f39", "#40a02b", "#df8e1d", "#1e66f5", "#ea76cb", "#179299", "#bcc0cc",
+    ],
+};
@@ -0,0 +1,61 @@
+//! When each key of the command is pressed in the demo
+//!
+//! The book uses it to highlight the key that was just pressed
+
+use std::{fs, path::Path, time::Duration};
+
+use miette::miette;
+use serde::Serialize;
+
+use crate::parse_example::Example;
+
+/// A single key pressed in the demo
+#[derive(Serialize, Clone, Debug)]
+pub struct Keystroke {
+    /// Seconds since the start of the demo
+    pub time: f64,
+    /// The key in Helix notation, as written in the example.
+    ///
+    /// Keys which are remapped for the recording are shown as the original
+    pub key: String,
+}
+
+impl Example {
+    /// Name of the file with the timeline, relative to the generated directory
+    pub fn timeline_file(&self) -> String {
+        format!("{}.keys.json", self.name)
+    }
+
+    /// Keys of the command, where key `i` is pressed at `times[i]`
+    pub fn timeline(&self, times: &[Duration]) -> Vec<Keystroke> {
+        self.key_events
+            .iter()
+            .zip(times)
+            .map(|(key, time)| Keystroke {
+                time: time.as_secs_f64(),
+                key: key.notation(),
+            })
+            .collect()
+    }
+
+    /// Write the timeline into `dir`, where key `i` is pressed at `times[i]`
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be written
+    pub fn write_timeline(&self, times: &[Duration], dir: &Path) -> miette::Result<()> {
+        let file = dir.join(self.timeline_file());
+        fs::write(
+            &file,
+            serde_json::to_string(&self.timeline(times))
+                .map_err(|err| miette!("Failed to serialize the timeline: {err}"))?,
+        )
+        .map_err(|err| {
+            miette!(
+                "Failed to write `{}` for example `{}`: {err}",
+                file.display(),
+                self.name
+            )
+        })
+    }
+}
@@ -0,0 +1,480 @@
+//! Translate the keys of the examples into the keymap of the reader
+//!
+//! Many people remap keys in their Helix `config.toml`, so the keys of an example
+//! are not what they would press. Each key sequence of an example is looked up in
+//! the default [`Keymap`] to find the command it runs, and is replaced with the keys
+//! which run the same command in the keymap of the reader.
+//!
+//! Keys which are not looked up in the keymap, such as the text typed into a prompt,
+//! stay as they are.
+
+use std::{fs, ops::Range, path::Path};
+
+use miette::miette;
+
+use crate::{
+    helix_mode::Mode,
+    helix_version::Version,
+    keymap::{self, Keymap},
+    parse_example::Example,
+    parse_helix_keys::{KeyEvent, parse_keys},
+};
+
+/// Command which is not bound to any keys in the keymap of the reader
+#[derive(Clone, Debug)]
+pub struct Unbound {
+    /// Name of the command
+    pub command: String,
+    /// Mode in which the command is used
+    pub mode: Mode,
+    /// Keys of the command in the default keymap, which are kept
+    pub keys: String,
+    /// Range of [`Example::key_events`]
+    pub key_events: Range<usize>,
+}
+
+/// An example, with the keys that the reader would press
+#[derive(Clone, Debug)]
+pub struct Translation {
+    /// The markdown file, with the command block and the inline code
+    /// of the explanation list rewritten
+    pub markdown: String,
+    /// Commands which the reader has no keys for
+    pub unbound: Vec<Unbound>,
+}
+
+/// The default keymap of Helix `version`, or of the latest release whose keymap is known,
+/// and the keymap of the reader, which is the default one with the Helix `config` applied
+///
+/// # Errors
+///
+/// If the keymap of the `version` is not known, or the config cannot be read
+pub fn keymaps(config: &Path, version: Option<Version>) -> miette::Result<(Keymap, Keymap)> {
+    let version = version
+        .or_else(|| keymap::versions().into_iter().max())
+        .unwrap_or_default();
+    let default = Keymap::of(version)?;
+    let config = fs::read_to_string(config)
+        .map_err(|err| miette!("Failed to read `{}`: {err}", config.display()))?;
+    let reader = default.with_config(&config)?;
+
+    Ok((default, reader))
+}
+
+impl Keymap {
+    /// This keymap, with the `[keys]` of a Helix `config.toml` applied on top of it.
+    ///
+    /// Unlike the default keymap, the keymap which is returned lists all of
+    /// the keys of select mode, as the config changes normal and select mode separately
+    ///
+    /// # Errors
+    ///
+    /// If the config is not valid TOML, or it has keys which cannot be parsed
+    pub fn with_config(&self, config: &str) -> miette::Result<Self> {
+        let config = config
+            .parse::<toml::Value>()
+            .map_err(|err| miette!("invalid Helix config: {err}"))?;
+
+        let mut keymap = self.clone();
+        for ((mode, keys), command) in &self.bindings {
+            if *mode == Mode::Normal {
+                keymap
+                    .bindings
+                    .entry((Mode::Select, keys.clone()))
+                    .or_insert_with(|| command.clone());
+            }
+        }
+
+        for (mode, name) in [
+            (Mode::Normal, "normal"),
+            (Mode::Select, "select"),
+            (Mode::Insert, "insert"),
+        ] {
+            if let Some(table) = config.get("keys").and_then(|keys| keys.get(name)) {
+                keymap.bind(mode, "", table)?;
+            }
+        }
+
+        Ok(keymap)
+    }
+
+    /// Bind the keys which start with `prefix` in the `mode` to the `value` of the config
+    fn bind(&mut self, mode: Mode, prefix: &str, value: &toml::Value) -> miette::Result<()> {
+        let command = match value {
+            toml::Value::Table(table) => {
+                // A sub-menu replaces the command of its keys
+                self.bindings.remove(&(mode, prefix.to_string()));
+                for (key, value) in table {
+                    // Only the name of the menu, not a key
+                    if key == "label" && value.is_str() {
+                        continue;
+                    }
+                    self.bind(mode, &format!("{prefix}{}", config_key(key)?), value)?;
+                }
+                return Ok(());
+            }
+            toml::Value::String(command) => Some(command.clone()),
+            // A sequence of a single command
+            toml::Value::Array(commands) if commands.len() == 1 => {
+                commands[0].as_str().map(ToString::to_string)
+            }
+            _ => None,
+        };
+
+        // A command replaces the sub-menu of its keys, and the commands of its prefixes
+        self.bindings.retain(|(bound_mode, keys), _| {
+            *bound_mode != mode
+                || !(keys.starts_with(prefix) || (prefix.starts_with(keys.as_str())))
+        });
+        if let Some(command) = command.filter(|command| command != "no_op") {
+            self.bindings.insert((mode, prefix.to_string()), command);
+        }
+
+        Ok(())
+    }
+
+    /// Keys which run the `command` in the `mode`, the shortest first
+    pub fn keys_of(&self, mode: Mode, command: &str) -> Vec<&str> {
+        let mut keys = self
+            .bindings
+            .iter()
+            .filter(|((bound_mode, _), bound)| *bound_mode == mode && *bound == command)
+            .map(|((_, keys), _)| keys.as_str())
+            .collect::<Vec<_>>();
+        keys.sort_by_key(|keys| {
+            (
+                parse_keys(keys, "keymap").map_or(usize::MAX, |keys| keys.len()),
+                keys.len(),
+            )
+        });
+        keys
+    }
+}
+
+/// A key as it is written in a Helix config, such as `C-s` or `ret`, in the notation of the examples
+fn config_key(key: &str) -> miette::Result<String> {
+    let notation = if key.chars().count() == 1 {
+        match key {
+            "<" => "<lt>".to_string(),
+            ">" => "<gt>".to_string(),
+            key => key.to_string(),
+        }
+    } else {
+        let mut tokens = key.split('-').collect::<Vec<_>>();
+        // `C--` is control and minus
+        let code = match tokens.pop() {
+            Some("") => {
+                tokens.pop();
+                "minus"
+            }
+            Some("ret") => "enter",
+            Some(code) => code,
+            None => "",
+        };
+        let modifiers = tokens
+            .into_iter()
+            .map(|modifier| match modifier {
+                "C" => "ctrl-",
+                "A" => "alt-",
+                "S" => "shift-",
+                "Meta" | "Cmd" | "Win" => "Meta-",
+                other => other,
+            })
+            .collect::<String>();
+        format!("<{modifiers}{code}>")
+    };
+
+    Ok(parse_keys(&notation, "config.toml")?
+        .iter()
+        .map(KeyEvent::notation)
+        .collect())
+}
+
+impl Example {
+    /// Rewrite the keys of this example, which are written for the `default` keymap,
+    /// into the keys which run the same commands in the `reader`'s keymap
+    pub fn translate(&self, default: &Keymap, reader: &Keymap) -> Translation {
+        // Notation of each key event, or an empty string if it is a part of
+        // a previous key event which is translated into other keys
+        let mut notations = self
+            .key_events
+            .iter()
+            .map(KeyEvent::notation)
+            .collect::<Vec<_>>();
+        let mut unbound = Vec::new();
+
+        for sequence in self.key_sequences() {
+            let Some(command) = default.command(sequence.mode, &sequence.keys) else {
+                continue;
+            };
+            // Keep the keys when the reader has not changed them
+            if reader
+                .bindings
+                .get(&(sequence.mode, sequence.keys.clone()))
+                .is_some_and(|bound| bound == command)
+            {
+                continue;
+            }
+
+            if let Some(keys) = reader.keys_of(sequence.mode, command).first() {
+                for (i, index) in sequence.key_events.clone().enumerate() {
+                    notations[index] = if i == 0 {
+                        (*keys).to_string()
+                    } else {
+                        String::new()
+                    };
+                }
+            } else {
+                unbound.push(Unbound {
+                    command: command.to_string(),
+                    mode: sequence.mode,
+                    keys: sequence.keys,
+                    key_events: sequence.key_events,
+                });
+            }
+        }
+
+        let original = self
+            .key_events
+            .iter()
+            .map(KeyEvent::notation)
+            .collect::<Vec<_>>();
+        let changed = |range: &Range<usize>| notations[range.clone()] != original[range.clone()];
+
+        // Byte range in the markdown, with the key events that it contains
+        let mut replacements = Vec::new();
+        let command_keys = 0..self.key_events.len();
+        if changed(&command_keys) {
+            replacements.push((
+                self.command_span.clone(),
+                command_block(&self.contents[self.command_span.clone()], &notations),
+            ));
+        }
+        for step in &self.steps {
+            let mut start = step.key_events.start;
+            for span in &step.code_spans {
+                let text = &self.contents[span.clone()];
+                let len =
+                    parse_keys(code_value(text).trim(), &self.name).map_or(0, |keys| keys.len());
+                let keys = start..(start + len).min(notations.len());
+                if changed(&keys) {
+                    replacements.push((span.clone(), code(text, &notations[keys].concat())));
+                }
+                start += len;
+            }
+        }
+
+        // Tell the reader which keys are not theirs, right after the command
+        if !unbound.is_empty() {
+            let note = unbound
+                .iter()
+                .map(|unbound| {
+                    format!(
+                        "> **Note:** your keymap has no keys for `{}` in {} mode, \
+                        so the default keys `{}` are shown.",
+                        unbound.command,
+                        format!("{:?}", unbound.mode).to_lowercase(),
+                        unbound.keys
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n>\n");
+            replacements.push((
+                self.command_span.end..self.command_span.end,
+                format!("\n\n{note}"),
+            ));
+        }
+
+        // Replace from the end, so the offsets of earlier replacements stay valid
+        replacements.sort_by_key(|(span, _)| span.start);
+        let mut markdown = self.contents.clone();
+        for (span, replacement) in replacements.into_iter().rev() {
+            markdown.replace_range(span, &replacement);
+        }
+
+        Translation { markdown, unbound }
+    }
+}
+
+/// Keys inside of an inline code or a code block
+pub(crate) fn code_value(text: &str) -> &str {
+    if text.contains('\n') {
+        let start = text.find('\n').map_or(0, |i| i + 1);
+        let end = text.rfind('\n').unwrap_or(text.len()).max(start);
+        &text[start..end]
+    } else {
+        text.trim_matches('`')
+    }
+}
+
+/// The inline code or code block `text`, with its keys replaced by `keys`
+fn code(text: &str, keys: &str) -> String {
+    if text.contains('\n') {
+        let value = code_value(text);
+        let indent = &value[..value.len() - value.trim_start().len()];
+        let start = text.find('\n').map_or(0, |i| i + 1);
+        let end = text.rfind('\n').unwrap_or(text.len()).max(start);
+        return format!("{}{indent}{keys}{}", &text[..start], &text[end..]);
+    }
+
+    // The fence must be longer than any backticks inside of the keys
+    let fence = "`".repeat(
+        keys.split(|ch| ch != '`')
+            .map(str::len)
+            .max()
+            .unwrap_or_default()
+            + 1,
+    );
+    if keys.starts_with('`') || keys.ends_with('`') {
+        format!("{fence} {keys} {fence}")
+    } else {
+        format!("{fence}{keys}{fence}")
+    }
+}
+
+/// The code block after `## Command`, with the key events replaced by `notations`.
+///
+/// Each line keeps the same key events, so the line breaks stay where they were
+fn command_block(text: &str, notations: &[String]) -> String {
+    let value = code_value(text);
+    let mut start = 0;
+    let mut lines = String::new();
+    for (i, line) in value.split('\n').enumerate() {
+        if i != 0 {
+            lines.push('\n');
+        }
+        let Ok(keys) = parse_keys(line, "command") else {
+            // Keys that are broken across lines
+            return code(text, &notations.concat());
+        };
+        let end = (start + keys.len()).min(notations.len());
+        lines.push_str(&notations[start..end].concat());
+        start = end;
+    }
+    code(text, &lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[keys.normal]
+C-s = "save_selection"
+j = "move_visual_line_up"
+k = "move_visual_line_down"
+x = "no_op"
+A-minus = "extend_line_below"
+"C--" = "split_selection"
+ret = ["goto_word"]
+
+[keys.normal.space]
+label = "space"
+w = "file_picker"
+
+[keys.insert]
+C-w = "delete_word_backward"
+"#;
+
+    fn keymaps() -> (Keymap, Keymap) {
+        let default = Keymap::of("25.01".parse().unwrap()).unwrap();
+        let reader = default.with_config(CONFIG).unwrap();
+        (default, reader)
+    }
+
+    #[test]
+    fn config_keys_in_the_notation_of_the_examples() {
+        assert_eq!(config_key("j").unwrap(), "j");
+        assert_eq!(config_key("<").unwrap(), "<lt>");
+        assert_eq!(config_key(">").unwrap(), ">");
+        assert_eq!(config_key("C-s").unwrap(), "<ctrl-s>");
+        assert_eq!(config_key("A-S-x").unwrap(), "<alt-X>");
+        assert_eq!(config_key("ret").unwrap(), "<enter>");
+        assert_eq!(config_key("C--").unwrap(), "<ctrl-minus>");
+        assert!(config_key("C-nothing").is_err());
+    }
+
+    #[test]
+    fn config_remaps_keys() {
+        let (_, reader) = keymaps();
+
+        assert_eq!(
+            reader.command(Mode::Normal, "j"),
+            Some("move_visual_line_up")
+        );
+        assert_eq!(
+            reader.command(Mode::Normal, "<ctrl-s>"),
+            Some("save_selection")
+        );
+        assert_eq!(reader.command(Mode::Normal, "<enter>"), Some("goto_word"));
+        assert_eq!(
+            reader.command(Mode::Normal, "<space>w"),
+            Some("file_picker")
+        );
+        assert_eq!(
+            reader.command(Mode::Insert, "<ctrl-w>"),
+            Some("delete_word_backward")
+        );
+        // `no_op` unbinds the keys
+        assert_eq!(reader.command(Mode::Normal, "x"), None);
+        assert_eq!(
+            reader.keys_of(Mode::Normal, "extend_line_below"),
+            ["<alt-minus>"]
+        );
+    }
+
+    #[test]
+    fn config_of_normal_mode_leaves_select_mode_alone() {
+        let (default, reader) = keymaps();
+        assert_eq!(
+            reader.command(Mode::Select, "x"),
+            default.command(Mode::Select, "x")
+        );
+        assert!(
+            reader
+                .bindings
+                .contains_key(&(Mode::Select, "d".to_string()))
+        );
+    }
+
+    #[test]
+    fn commands_replace_their_sub_menus() {
+        let (default, _) = keymaps();
+        let reader = default
+            .with_config("[keys.normal]\ng = \"goto_file_start\"")
+            .unwrap();
+
+        assert_eq!(reader.command(Mode::Normal, "g"), Some("goto_file_start"));
+        assert_eq!(reader.command(Mode::Normal, "gg"), None);
+    }
+
+    #[test]
+    fn invalid_configs() {
+        let (default, _) = keymaps();
+        assert!(default.with_config("[keys.normal").is_err());
+        assert!(
+            default
+                .with_config("[keys.normal]\nC-nothing = \"no_op\"")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn translates_into_the_keymap_of_the_reader() {
+        let (default, reader) = keymaps();
+        let example = Example::from_markdown(
+            "# Test\n\n## Before\n\n```js\na\n```\n\n## After\n\n```js\nb\n```\n\n\
+            ## Command\n\n```\njjxd\n```\n\n1. `jj` goes down\n1. `xd` deletes the line\n"
+                .to_string(),
+            "test.md",
+            "test",
+        )
+        .unwrap();
+
+        let translation = example.translate(&default, &reader);
+        assert!(translation.markdown.contains("```\nkk<alt-minus>d\n```"));
+        assert!(translation.markdown.contains("1. `kk` goes down"));
+        assert!(translation.markdown.contains("1. `<alt-minus>d` deletes"));
+        assert!(translation.unbound.is_empty());
+    }
+}